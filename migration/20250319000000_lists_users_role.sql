@@ -0,0 +1 @@
+ALTER TABLE lists_users ADD COLUMN role TEXT NOT NULL DEFAULT 'editor'; -- owner, editor or viewer
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS invitations (
+    id TEXT NOT NULL PRIMARY KEY, -- UUID
+    list_id TEXT NOT NULL, -- UUID
+    role TEXT NOT NULL, -- owner, editor or viewer
+    created_at TEXT NOT NULL, -- DATETIME
+    created_by TEXT NOT NULL, -- UUID
+    expires_at TEXT NOT NULL, -- DATETIME
+    revoked_at TEXT DEFAULT NULL -- DATETIME
+);
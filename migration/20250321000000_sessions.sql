@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT NOT NULL PRIMARY KEY, -- SESSION ID
+    user_id TEXT DEFAULT NULL, -- UUID
+    data TEXT NOT NULL, -- JSON
+    expires_at TEXT NOT NULL -- DATETIME
+);
+
+CREATE INDEX IF NOT EXISTS sessions_user_id ON sessions (user_id);
+CREATE INDEX IF NOT EXISTS sessions_expires_at ON sessions (expires_at);
@@ -0,0 +1,8 @@
+ALTER TABLE users ADD COLUMN totp_secret TEXT DEFAULT NULL; -- BASE32
+
+CREATE TABLE IF NOT EXISTS recovery_codes (
+    user_id TEXT NOT NULL, -- UUID
+    code_hash TEXT NOT NULL, -- SHA-256
+    used_at TEXT DEFAULT NULL, -- DATETIME
+    PRIMARY KEY (user_id, code_hash)
+);
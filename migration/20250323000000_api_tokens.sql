@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id TEXT NOT NULL PRIMARY KEY, -- UUID
+    user_id TEXT NOT NULL, -- UUID
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE, -- SHA-256
+    scope TEXT NOT NULL, -- read or write
+    created_at TEXT NOT NULL, -- DATETIME
+    last_used_at TEXT DEFAULT NULL -- DATETIME
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS user_identities (
+    issuer TEXT NOT NULL, -- URL
+    subject TEXT NOT NULL,
+    user_id TEXT NOT NULL, -- UUID
+    created_at TEXT NOT NULL, -- DATETIME
+    PRIMARY KEY (issuer, subject)
+);
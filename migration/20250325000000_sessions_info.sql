@@ -0,0 +1,5 @@
+ALTER TABLE sessions ADD COLUMN public_id TEXT DEFAULT NULL; -- UUID, safe to show unlike the session id
+ALTER TABLE sessions ADD COLUMN created_at TEXT DEFAULT NULL; -- DATETIME
+ALTER TABLE sessions ADD COLUMN last_seen_at TEXT DEFAULT NULL; -- DATETIME
+ALTER TABLE sessions ADD COLUMN user_agent TEXT DEFAULT NULL;
+ALTER TABLE sessions ADD COLUMN ip TEXT DEFAULT NULL;
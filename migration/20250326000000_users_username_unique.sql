@@ -0,0 +1,12 @@
+-- Usernames were already normalized with the Unicode rules of
+-- `normalize_username` before this runs, see `User::normalize_usernames`.
+-- This only covers databases migrated by other means, where SQLite folds
+-- ASCII letters alone.
+UPDATE users SET username = lower(trim(username));
+
+-- Keep the oldest of each group of colliding usernames and rename the
+-- others with a random suffix, within the 32 characters allowed.
+UPDATE users SET username = substr(username, 1, 23) || '-' || lower(hex(randomblob(4)))
+WHERE rowid NOT IN (SELECT MIN(rowid) FROM users GROUP BY username);
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_username ON users (username);
@@ -0,0 +1,11 @@
+ALTER TABLE users ADD COLUMN email TEXT DEFAULT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email ON users (email);
+
+CREATE TABLE IF NOT EXISTS password_resets (
+    token_hash TEXT NOT NULL PRIMARY KEY, -- SHA-256
+    user_id TEXT NOT NULL, -- UUID
+    created_at TEXT NOT NULL, -- DATETIME
+    expires_at TEXT NOT NULL, -- DATETIME
+    used_at TEXT DEFAULT NULL -- DATETIME
+);
@@ -0,0 +1,29 @@
+ALTER TABLE users ADD COLUMN time_zone TEXT NOT NULL DEFAULT 'UTC'; -- IANA name
+
+-- Due dates become optional calendar days, with an optional exact instant
+-- for tasks due at a given time. SQLite can't drop the NOT NULL constraint
+-- in place, so the table is rebuilt. Existing due dates were stored as
+-- midnight UTC of the chosen day.
+CREATE TABLE tasks_new (
+    id TEXT NOT NULL PRIMARY KEY, -- UUID
+    list_id TEXT NOT NULL, -- UUID
+    title TEXT NOT NULL,
+    due_date TEXT DEFAULT NULL, -- DATE
+    due_at TEXT DEFAULT NULL, -- DATETIME
+    created_at TEXT NOT NULL, -- DATETIME
+    created_by TEXT NOT NULL, -- UUID
+    completed_at TEXT DEFAULT NULL, -- DATETIME
+    completed_by TEXT DEFAULT NULL, -- UUID
+    deleted_at TEXT DEFAULT NULL -- DATETIME
+);
+
+INSERT INTO tasks_new (
+    id, list_id, title, due_date, created_at, created_by, completed_at, completed_by, deleted_at
+)
+SELECT
+    id, list_id, title, date(due_date), created_at, created_by, completed_at, completed_by, deleted_at
+FROM tasks;
+
+DROP TABLE tasks;
+
+ALTER TABLE tasks_new RENAME TO tasks;
@@ -0,0 +1,6 @@
+ALTER TABLE tasks ADD COLUMN recurrence TEXT DEFAULT NULL; -- RRULE
+
+-- Every occurrence of a recurring task shares the id of the first one.
+ALTER TABLE tasks ADD COLUMN series_id TEXT DEFAULT NULL; -- UUID
+
+CREATE INDEX IF NOT EXISTS tasks_series_id ON tasks (series_id);
@@ -0,0 +1,6 @@
+ALTER TABLE tasks ADD COLUMN parent_id TEXT DEFAULT NULL; -- UUID
+
+-- Whether the task completes itself once all its subtasks are done.
+ALTER TABLE tasks ADD COLUMN auto_complete BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS tasks_parent_id ON tasks (parent_id);
@@ -0,0 +1,15 @@
+-- 0 for none, then 1 to 3 from low to high.
+ALTER TABLE tasks ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+
+-- Manual order of the task among its siblings. Ranks are fractional so a
+-- task can be moved between two others by updating it alone.
+ALTER TABLE tasks ADD COLUMN position REAL NOT NULL DEFAULT 0;
+
+UPDATE tasks SET position = 1 + (
+    SELECT COUNT(*) FROM tasks AS siblings
+    WHERE siblings.list_id = tasks.list_id
+    AND siblings.parent_id IS tasks.parent_id
+    AND (siblings.created_at, siblings.id) < (tasks.created_at, tasks.id)
+);
+
+CREATE INDEX IF NOT EXISTS tasks_list_id_position ON tasks (list_id, parent_id, position);
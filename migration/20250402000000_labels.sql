@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS labels (
+    id TEXT NOT NULL PRIMARY KEY, -- UUID
+    user_id TEXT NOT NULL, -- UUID
+    name TEXT NOT NULL,
+    color TEXT NOT NULL, -- #rrggbb
+    created_at TEXT NOT NULL -- DATETIME
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS labels_user_id_name ON labels (user_id, name);
+
+CREATE TABLE IF NOT EXISTS tasks_labels (
+    task_id TEXT NOT NULL, -- UUID
+    label_id TEXT NOT NULL, -- UUID
+    PRIMARY KEY (task_id, label_id)
+);
+
+CREATE INDEX IF NOT EXISTS tasks_labels_label_id ON tasks_labels (label_id);
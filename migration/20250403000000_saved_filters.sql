@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS saved_filters (
+    id TEXT NOT NULL PRIMARY KEY, -- UUID
+    user_id TEXT NOT NULL, -- UUID
+    name TEXT NOT NULL,
+    query TEXT NOT NULL, -- see util::filter
+    created_at TEXT NOT NULL -- DATETIME
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS saved_filters_user_id_name ON saved_filters (user_id, name);
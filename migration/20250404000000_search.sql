@@ -0,0 +1,31 @@
+-- Full-text indexes of task titles and list titles, keyed by the id of the
+-- indexed row and kept in sync by the triggers below.
+CREATE VIRTUAL TABLE IF NOT EXISTS tasks_search USING fts5 (id UNINDEXED, title);
+CREATE VIRTUAL TABLE IF NOT EXISTS lists_search USING fts5 (id UNINDEXED, title);
+
+INSERT INTO tasks_search (id, title) SELECT id, title FROM tasks;
+INSERT INTO lists_search (id, title) SELECT id, title FROM lists;
+
+CREATE TRIGGER IF NOT EXISTS tasks_search_insert AFTER INSERT ON tasks BEGIN
+    INSERT INTO tasks_search (id, title) VALUES (new.id, new.title);
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_search_update AFTER UPDATE OF title ON tasks BEGIN
+    UPDATE tasks_search SET title = new.title WHERE id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_search_delete AFTER DELETE ON tasks BEGIN
+    DELETE FROM tasks_search WHERE id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS lists_search_insert AFTER INSERT ON lists BEGIN
+    INSERT INTO lists_search (id, title) VALUES (new.id, new.title);
+END;
+
+CREATE TRIGGER IF NOT EXISTS lists_search_update AFTER UPDATE OF title ON lists BEGIN
+    UPDATE lists_search SET title = new.title WHERE id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS lists_search_delete AFTER DELETE ON lists BEGIN
+    DELETE FROM lists_search WHERE id = old.id;
+END;
@@ -0,0 +1,3 @@
+-- Hashes from before this migration used a salt shared by every user.
+ALTER TABLE users ADD COLUMN password_needs_rehash INTEGER NOT NULL DEFAULT 0; -- BOOLEAN
+UPDATE users SET password_needs_rehash = 1;
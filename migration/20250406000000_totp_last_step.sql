@@ -0,0 +1,2 @@
+-- Last time step a code was accepted for, so each code only works once.
+ALTER TABLE users ADD COLUMN totp_last_step INTEGER DEFAULT NULL;
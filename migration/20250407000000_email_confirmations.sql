@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS email_confirmations (
+    token_hash TEXT NOT NULL PRIMARY KEY, -- SHA-256
+    user_id TEXT NOT NULL, -- UUID
+    email TEXT NOT NULL,
+    created_at TEXT NOT NULL, -- DATETIME
+    expires_at TEXT NOT NULL, -- DATETIME
+    used_at TEXT DEFAULT NULL -- DATETIME
+);
@@ -28,10 +28,3 @@ CREATE TABLE IF NOT EXISTS tasks (
     completed_at TEXT DEFAULT NULL, -- DATETIME
     completed_by TEXT DEFAULT NULL -- UUID
 );
-
--- CREATE TABLE IF NOT EXISTS sessions (
---     id TEXT NOT NULL PRIMARY KEY, -- UUID
---     user_id TEXT NOT NULL, -- UUID
---     created_at TEXT NOT NULL, -- DATETIME
---     expires_at TEXT NOT NULL -- DATETIME
--- );
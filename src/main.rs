@@ -4,12 +4,20 @@ mod repository;
 mod server;
 mod util;
 
-use chrono::NaiveDate;
+use chrono::Utc;
+use chrono_tz::{Tz, TZ_VARIANTS};
 use dioxus::prelude::*;
 use dioxus_sdk::storage::*;
 use uuid::Uuid;
 
-use crate::model::{List, Task};
+use crate::model::{
+    ActiveSession, ApiToken, Invite, Label, List, Member, Priority, Role, SavedFilter, Scope,
+    SmartView, Task, TaskLabel, TaskNode, TaskOrder, TotpEnrollment, User,
+};
+use crate::util::filter::Filter;
+use crate::util::recurrence::{parse_recurrence_input, Recurrence};
+use crate::util::time_zone::parse_due_inputs;
+use crate::util::validation::{normalize_email, validate_registration, Field, FieldErrors};
 
 #[derive(Debug, Clone, Routable, PartialEq)]
 #[rustfmt::skip]
@@ -19,10 +27,46 @@ enum Route {
     Home {},
     #[route("/lists/:id")]
     Lists { id: Uuid },
+    #[route("/today")]
+    Today {},
+    #[route("/upcoming")]
+    Upcoming {},
+    #[route("/overdue")]
+    Overdue {},
+    #[route("/completed")]
+    Completed {},
+    #[route("/trash")]
+    Trash {},
+    #[route("/labels")]
+    Labels {},
+    #[route("/labels/:id")]
+    LabelTasks { id: Uuid },
+    #[route("/filters")]
+    Filters {},
+    #[route("/filters/:id")]
+    FilterTasks { id: Uuid },
+    #[route("/search?:query")]
+    Search { query: String },
+    #[route("/invite/:token")]
+    Invitation { token: String },
     #[route("/user/login")]
     Login {},
     #[route("/user/register")]
     Register {},
+    #[route("/user/reset")]
+    ForgotPassword {},
+    #[route("/user/reset/:token")]
+    ResetPassword { token: String },
+    #[route("/user/email/:token")]
+    ConfirmEmail { token: String },
+    #[route("/user/oidc/callback?:code&:state")]
+    OidcCallback { code: String, state: String },
+    #[route("/user/settings")]
+    Settings {},
+    #[route("/user/sessions")]
+    Sessions {},
+    #[route("/user/delete")]
+    DeleteAccount {},
 }
 
 #[cfg(not(feature = "server"))]
@@ -35,19 +79,75 @@ fn main() {
 #[tokio::main]
 async fn main() {
     use std::any::Any;
-    use std::sync::Arc;
+    use std::env;
+    use std::net::SocketAddr;
+    use std::sync::{Arc, LazyLock};
+    use std::time::Duration;
 
     use axum::Router;
+    use dioxus::logger::tracing;
     use sqlx::SqlitePool;
-    use tower_sessions::{Expiry, MemoryStore, SessionManagerLayer};
+    use tower_sessions::session_store::ExpiredDeletion;
+    use tower_sessions::{Expiry, SessionManagerLayer};
+
+    use crate::repository::session::SqliteStore;
+
+    dioxus::logger::initialize_default();
+
+    LazyLock::force(&crate::util::signature::SECRET);
+    LazyLock::force(&crate::util::oidc::CONFIG);
+    LazyLock::force(&crate::util::mail::MAILER);
 
     let pool = SqlitePool::connect("sqlite://todo.db").await.unwrap();
+    // SQLite only lowercases ASCII, so usernames are normalized here before
+    // the migration making them unique.
+    User::normalize_usernames(&pool)
+        .await
+        .expect("Failed to normalize usernames");
     sqlx::migrate!("./migration").run(&pool).await.unwrap();
 
-    let session_store = MemoryStore::default();
+    // Sessions expire after `SESSION_EXPIRY` seconds of inactivity, or when
+    // the browser closes if unset.
+    let expiry = env::var("SESSION_EXPIRY")
+        .map(|seconds| seconds.parse().expect("Invalid session expiry"))
+        .map(|seconds| Expiry::OnInactivity(time::Duration::seconds(seconds)))
+        .unwrap_or(Expiry::OnSessionEnd);
+
+    let session_store = SqliteStore::new(pool.clone());
+
+    let cleanup_store = session_store.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            if let Err(error) = cleanup_store.delete_expired().await {
+                tracing::error!("Failed to delete expired sessions: {error}");
+            }
+        }
+    });
+
+    // Deleted lists stay in the trash for `LIST_RETENTION_DAYS` days, 30 if
+    // unset, before being purged along with their tasks.
+    let retention = env::var("LIST_RETENTION_DAYS")
+        .map(|days| days.parse().expect("Invalid list retention"))
+        .map(chrono::Duration::days)
+        .unwrap_or(chrono::Duration::days(30));
+
+    let purge_pool = pool.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            let deleted_before = Utc::now() - retention;
+            if let Err(error) = List::purge_deleted_lists(deleted_before, &purge_pool).await {
+                tracing::error!("Failed to purge deleted lists: {error}");
+            }
+        }
+    });
+
     let session_layer = SessionManagerLayer::new(session_store)
         .with_secure(false)
-        .with_expiry(Expiry::OnSessionEnd);
+        .with_expiry(expiry);
 
     let pool_provider = move || Box::new(pool.clone()) as Box<dyn Any>;
 
@@ -57,7 +157,7 @@ async fn main() {
     let router = Router::new()
         .serve_dioxus_application(config, App)
         .layer(session_layer)
-        .into_make_service();
+        .into_make_service_with_connect_info::<SocketAddr>();
 
     let socket_addr = dioxus::cli_config::fullstack_address_or_localhost();
 
@@ -72,7 +172,12 @@ async fn main() {
 
 #[component]
 fn App() -> Element {
-    use_context_provider(|| Signal::new(false));
+    // Resolved during SSR and carried over on hydration, so a valid session
+    // cookie keeps the user logged in across reloads and deep links.
+    let current_user = use_server_future(server::current_user)?;
+    let user = current_user.read().clone().and_then(Result::ok).flatten();
+
+    use_context_provider(|| Signal::new(user));
 
     rsx! {
         Router::<Route> {}
@@ -81,17 +186,71 @@ fn App() -> Element {
 
 #[component]
 fn Navbar() -> Element {
-    let mut auth = use_context::<Signal<bool>>();
+    let mut auth = use_context::<Signal<Option<User>>>();
+
+    // Shared with the filters page, which refreshes it after each change.
+    let mut saved_filters = use_context_provider(|| Signal::new(Vec::<SavedFilter>::new()));
+
+    let mut search_query = use_signal(String::new);
+
+    use_effect(move || {
+        if auth.read().is_some() {
+            spawn(async move {
+                saved_filters.set(server::get_saved_filters().await.unwrap_or_default());
+            });
+        } else {
+            saved_filters.set(Vec::new());
+        }
+    });
 
     rsx! {
         nav {
             Link { to: Route::Home {}, "Home" }
-            if auth.read().clone() {
+            if let Some(user) = auth.read().as_ref() {
+                " "
+                span { "{user.username}" }
+                " "
+                Link { to: Route::Today {}, "Today" }
+                " "
+                Link { to: Route::Upcoming {}, "Upcoming" }
+                " "
+                Link { to: Route::Overdue {}, "Overdue" }
+                " "
+                Link { to: Route::Completed {}, "Completed" }
+                " "
+                Link { to: Route::Labels {}, "Labels" }
+                " "
+                Link { to: Route::Filters {}, "Filters" }
+                " "
+                Link { to: Route::Trash {}, "Trash" }
+                " "
+                Link { to: Route::Settings {}, "Settings" }
+                " "
+                form { style: "display: inline",
+                    input {
+                        r#type: "search",
+                        placeholder: "search tasks and lists",
+                        value: "{search_query}",
+                        oninput: move |event| search_query.set(event.value()),
+                    }
+                    " "
+                    button {
+                        r#type: "submit",
+                        onclick: move |event| {
+                            event.prevent_default();
+                            let query = search_query.read().trim().to_owned();
+                            if !query.is_empty() {
+                                navigator().push(Route::Search { query });
+                            }
+                        },
+                        "Search"
+                    }
+                }
                 " "
                 button {
                     onclick: move |_| async move {
                         server::logout().await.expect("Failed to logout");
-                        auth.set(false);
+                        auth.set(None);
                         navigator().push(Route::Home {});
                     },
                     "Log out"
@@ -103,6 +262,20 @@ fn Navbar() -> Element {
                 Link { to: Route::Register {}, "Register" }
             }
         }
+        if auth.read().is_some() && !saved_filters.read().is_empty() {
+            aside {
+                "Saved filters:"
+                for saved_filter in saved_filters.read().clone() {
+                    " "
+                    Link {
+                        to: Route::FilterTasks {
+                            id: saved_filter.id,
+                        },
+                        "{saved_filter.name}"
+                    }
+                }
+            }
+        }
 
         Outlet::<Route> {}
     }
@@ -110,9 +283,9 @@ fn Navbar() -> Element {
 
 #[component]
 fn Home() -> Element {
-    let auth = use_context::<Signal<bool>>();
+    let auth = use_context::<Signal<Option<User>>>();
 
-    if !auth.read().clone() {
+    if auth.read().is_none() {
         return rsx! {
             div {
                 h1 { "Welcome!" }
@@ -121,10 +294,12 @@ fn Home() -> Element {
         };
     }
 
-    let mut lists =
-        use_synced_storage::<LocalStorage, Vec<List>>("lists".to_string(), || Vec::new());
+    let mut lists = use_synced_storage::<LocalStorage, Vec<List>>("lists".to_string(), Vec::new);
 
-    let mut list_name = use_signal(|| String::new());
+    let mut list_name = use_signal(String::new);
+
+    let mut editing = use_signal(|| None::<Uuid>);
+    let mut edit_name = use_signal(String::new);
 
     let update_lists = move || async move {
         lists.set(server::get_lists().await.expect("Failed to get lists"));
@@ -136,17 +311,69 @@ fn Home() -> Element {
         ul {
             for list in lists.read().clone() {
                 li {
-                    Link { to: Route::Lists { id: list.id }, "{list.title}" }
-                    " "
-                    button {
-                        onclick: move |_| {
-                            let list_clone = list.clone();
-                            async move {
-                                server::delete_list(list_clone).await.expect("Failed to delete list");
-                                update_lists().await;
+                    if *editing.read() == Some(list.id) {
+                        form {
+                            input {
+                                r#type: "text",
+                                value: "{edit_name}",
+                                oninput: move |event| edit_name.set(event.value()),
                             }
-                        },
-                        "Delete"
+                            " "
+                            button {
+                                r#type: "submit",
+                                onclick: move |event| {
+                                    event.prevent_default();
+                                    let list_id = list.id;
+                                    async move {
+                                        let title = edit_name.read().clone();
+                                        if title.trim().is_empty() {
+                                            return;
+                                        }
+                                        let Some(list) = lists.read().iter().find(|list| list.id == list_id).cloned() else {
+                                            return;
+                                        };
+                                        server::rename_list(List { title, ..list })
+                                            .await
+                                            .expect("Failed to rename list");
+                                        editing.set(None);
+                                        update_lists().await;
+                                    }
+                                },
+                                "Save"
+                            }
+                            " "
+                            button {
+                                r#type: "button",
+                                onclick: move |_| editing.set(None),
+                                "Cancel"
+                            }
+                        }
+                    } else {
+                        Link { to: Route::Lists { id: list.id }, "{list.title}" }
+                    }
+                    if list.role == Role::Owner && *editing.read() != Some(list.id) {
+                        " "
+                        button {
+                            onclick: move |_| {
+                                let list_id = list.id;
+                                if let Some(list) = lists.read().iter().find(|list| list.id == list_id) {
+                                    edit_name.set(list.title.clone());
+                                }
+                                editing.set(Some(list_id));
+                            },
+                            "Rename"
+                        }
+                        " "
+                        button {
+                            onclick: move |_| {
+                                let list_clone = list.clone();
+                                async move {
+                                    server::delete_list(list_clone).await.expect("Failed to delete list");
+                                    update_lists().await;
+                                }
+                            },
+                            "Delete"
+                        }
                     }
                 }
             }
@@ -178,216 +405,2358 @@ fn Home() -> Element {
 
 #[component]
 fn Lists(id: Uuid) -> Element {
-    let auth = use_context::<Signal<bool>>();
+    let auth = use_context::<Signal<Option<User>>>();
 
-    if !auth.read().clone() {
+    let Some(tz) = auth.read().as_ref().map(User::tz) else {
         navigator().push(Route::Home {});
         return rsx! {};
-    }
+    };
 
     let mut tasks =
-        use_synced_storage::<LocalStorage, Vec<Task>>(format!("tasks_{}", id), || Vec::new());
+        use_synced_storage::<LocalStorage, Vec<TaskNode>>(format!("task_tree_{}", id), || {
+            Vec::new()
+        });
+
+    let mut task_name = use_signal(String::new);
+    let mut due_date = use_signal(String::new);
+    let mut due_time = use_signal(String::new);
+    let mut recurrence = use_signal(String::new);
+    let mut priority = use_signal(Priority::default);
+
+    let mut order =
+        use_synced_storage::<LocalStorage, TaskOrder>("task_order".to_string(), TaskOrder::default);
+    let dragged = use_signal(|| None::<Uuid>);
+
+    let mut labels = use_signal(Vec::<Label>::new);
+    let mut task_labels = use_signal(Vec::<TaskLabel>::new);
+
+    let mut members =
+        use_synced_storage::<LocalStorage, Vec<Member>>(format!("members_{}", id), Vec::new);
 
-    let mut task_name = use_signal(|| String::new());
-    let mut due_date = use_signal(|| String::new());
+    let mut member_name = use_signal(String::new);
+    let mut member_role = use_signal(|| Role::Editor);
+    let mut message = use_signal(String::new);
+
+    let mut invites = use_signal(Vec::<(Invite, String)>::new);
+    let mut invite_role = use_signal(|| Role::Editor);
+
+    let current_role = use_resource(move || async move { server::get_role(id).await.ok() });
+    let role = current_role().flatten().unwrap_or(Role::Viewer);
 
     let update_tasks = move || async move {
-        tasks.set(server::get_tasks(id).await.expect("Failed to get tasks"));
+        tasks.set(
+            server::get_task_tree(id)
+                .await
+                .expect("Failed to get tasks"),
+        );
+    };
+
+    let update_labels = move || async move {
+        labels.set(server::get_labels().await.expect("Failed to get labels"));
+        task_labels.set(
+            server::get_task_labels(id)
+                .await
+                .expect("Failed to get task labels"),
+        );
+    };
+
+    let update_members = move || async move {
+        members.set(
+            server::get_members(id)
+                .await
+                .expect("Failed to get members"),
+        );
+    };
+
+    let update_invites = move || async move {
+        // Only owners may see invites, so anyone else just gets an empty list.
+        invites.set(server::get_invites(id).await.unwrap_or_default());
     };
 
     use_future(update_tasks);
+    use_future(update_labels);
+    use_future(update_members);
+    use_future(update_invites);
+
+    // Every task of the list, indented under its parent, to pick a new parent
+    // from when moving a task.
+    let all_tasks: Vec<(Uuid, String)> = TaskNode::flatten(&tasks.read())
+        .into_iter()
+        .map(|(depth, node)| {
+            (
+                node.task.id,
+                format!("{}{}", "— ".repeat(depth), node.task.title),
+            )
+        })
+        .collect();
+
+    let mut sorted_tasks = tasks.read().clone();
+    TaskNode::sort(&mut sorted_tasks, *order.read());
 
     rsx! {
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        label {
+            "Sort by "
+            select {
+                value: order.read().as_str(),
+                onchange: move |event| {
+                    if let Ok(new_order) = event.value().parse() {
+                        order.set(new_order);
+                    }
+                },
+                for option_order in TaskOrder::ALL {
+                    option { value: option_order.as_str(), {option_order.as_str()} }
+                }
+            }
+        }
+        ul {
+            for node in sorted_tasks {
+                TaskItem {
+                    key: "{node.task.id}",
+                    node,
+                    tasks: all_tasks.clone(),
+                    tz,
+                    role,
+                    order: *order.read(),
+                    dragged,
+                    labels: labels.read().clone(),
+                    task_labels: task_labels.read().clone(),
+                    message,
+                    on_update: move |_| {
+                        spawn(update_tasks());
+                        spawn(update_labels());
+                    },
+                }
+            }
+        }
+        if role >= Role::Editor {
+            form {
+                input {
+                    r#type: "text",
+                    placeholder: "task name",
+                    value: "{task_name}",
+                    oninput: move |event| task_name.set(event.value()),
+                }
+                " "
+                input {
+                    r#type: "date",
+                    placeholder: "due date",
+                    value: "{due_date}",
+                    oninput: move |event| due_date.set(event.value()),
+                }
+                " "
+                input {
+                    r#type: "time",
+                    placeholder: "due time",
+                    value: "{due_time}",
+                    oninput: move |event| due_time.set(event.value()),
+                }
+                " "
+                input {
+                    r#type: "text",
+                    placeholder: "repeat, e.g. FREQ=WEEKLY;BYDAY=MO",
+                    value: "{recurrence}",
+                    oninput: move |event| recurrence.set(event.value()),
+                }
+                " "
+                select {
+                    value: priority.read().as_str(),
+                    onchange: move |event| {
+                        if let Ok(new_priority) = event.value().parse() {
+                            priority.set(new_priority);
+                        }
+                    },
+                    for option_priority in Priority::ALL {
+                        option { value: option_priority.as_str(), {option_priority.as_str()} }
+                    }
+                }
+                " "
+                button {
+                    r#type: "submit",
+                    onclick: move |event| {
+                        event.prevent_default();
+                        async move {
+                            let task_name = task_name.read().clone();
+                            if task_name.is_empty() {
+                                return;
+                            }
+                            let Some((due_date, due_time)) = parse_due_inputs(
+                                &due_date.read(),
+                                &due_time.read(),
+                            ) else {
+                                return;
+                            };
+                            let recurrence = recurrence.read().trim().to_owned();
+                            if let Err(error) = parse_recurrence_input(&recurrence) {
+                                message.set(format!("Invalid repeat rule: {error}"));
+                                return;
+                            }
+                            let recurrence = Some(recurrence).filter(|recurrence| !recurrence.is_empty());
+                            if server::create_task(
+                                    task_name,
+                                    due_date,
+                                    due_time,
+                                    recurrence,
+                                    *priority.read(),
+                                    None,
+                                    id,
+                                )
+                                .await
+                                .is_err()
+                            {
+                                message.set("Failed to create task".to_owned());
+                            }
+                            update_tasks().await;
+                        }
+                    },
+                    "Create"
+                }
+            }
+        }
+        h2 { "Shared with" }
         ul {
-            for task in tasks.read().clone() {
+            for member in members.read().clone() {
                 li {
-                    input {
-                        r#type: "checkbox",
-                        checked: "{task.completed_at.is_some()}",
-                        onchange: move |_| {
-                            let task_clone = task.clone();
-                            async move {
-                                if task_clone.completed_at.is_none() {
-                                    server::complete_task(task_clone)
-                                        .await
-                                        .expect("Failed to complete task");
-                                } else {
-                                    server::uncomplete_task(task_clone)
-                                        .await
-                                        .expect("Failed to uncomplete task");
+                    span { "{member.username}" }
+                    " ("
+                    span { {member.role.as_str()} }
+                    ")"
+                    if role == Role::Owner {
+                        " "
+                        button {
+                            onclick: move |_| {
+                                let member_clone = member.clone();
+                                async move {
+                                    if server::unshare_list(member_clone).await.is_err() {
+                                        message.set("Failed to remove member".to_owned());
+                                    }
+                                    update_members().await;
                                 }
-                                update_tasks().await;
-                            }
-                        },
-                    }
-                    " "
-                    span { "{task.title}" }
-                    div {
-                        "Due on: "
-                        span { {task.due_date.format("%Y-%m-%d").to_string()} }
-                    }
-                    if let (Some(completed_at), Some(completed_by)) = (
-                        task.completed_at,
-                        task.completed_by.as_ref(),
-                    )
-                    {
-                        div {
-                            "Completed at: "
-                            span { {completed_at.format("%Y-%m-%d %H:%M:%S").to_string()} }
-                            " by "
-                            span { {completed_by.to_string()} }
+                            },
+                            "Remove"
                         }
                     }
                 }
             }
         }
-        form {
-            input {
-                r#type: "text",
-                placeholder: "task name",
-                value: "{task_name}",
-                oninput: move |event| task_name.set(event.value()),
-            }
-            " "
-            input {
-                r#type: "date",
-                placeholder: "due date",
-                value: "{due_date}",
-                oninput: move |event| due_date.set(event.value()),
+        if role == Role::Owner {
+            form {
+                input {
+                    r#type: "text",
+                    placeholder: "username",
+                    value: "{member_name}",
+                    oninput: move |event| member_name.set(event.value()),
+                }
+                " "
+                select {
+                    value: member_role.read().as_str(),
+                    onchange: move |event| {
+                        if let Ok(role) = event.value().parse() {
+                            member_role.set(role);
+                        }
+                    },
+                    for option_role in Role::ALL {
+                        option { value: option_role.as_str(), {option_role.as_str()} }
+                    }
+                }
+                " "
+                button {
+                    r#type: "submit",
+                    onclick: move |event| {
+                        event.prevent_default();
+                        async move {
+                            let username = member_name.read().clone();
+                            if username.is_empty() {
+                                return;
+                            }
+                            if server::share_list(id, username, *member_role.read()).await.is_err() {
+                                message.set("Failed to share list".to_owned());
+                            } else {
+                                message.set(String::new());
+                                member_name.set(String::new());
+                            }
+                            update_members().await;
+                        }
+                    },
+                    "Share"
+                }
             }
-            " "
-            button {
-                r#type: "submit",
-                onclick: move |event| {
-                    event.prevent_default();
-                    async move {
-                        let task_name = task_name.read().clone();
-                        let due_date = due_date.read().clone();
-                        if task_name.is_empty() {
-                            return;
+            h2 { "Invite links" }
+            ul {
+                for (invite, token) in invites.read().clone() {
+                    li {
+                        Link { to: Route::Invitation { token: token.clone() }, "/invite/{token}" }
+                        " ("
+                        span { {invite.role.as_str()} }
+                        ", expires "
+                        span { {invite.expires_at.format("%Y-%m-%d %H:%M").to_string()} }
+                        ") "
+                        button {
+                            onclick: move |_| {
+                                let invite_clone = invite.clone();
+                                async move {
+                                    if server::revoke_invite(invite_clone).await.is_err() {
+                                        message.set("Failed to revoke invite".to_owned());
+                                    }
+                                    update_invites().await;
+                                }
+                            },
+                            "Revoke"
                         }
-                        if due_date.is_empty() {
-                            return;
+                    }
+                }
+            }
+            form {
+                select {
+                    value: invite_role.read().as_str(),
+                    onchange: move |event| {
+                        if let Ok(role) = event.value().parse() {
+                            invite_role.set(role);
                         }
-                        let due_date = NaiveDate::parse_from_str(&due_date, "%Y-%m-%d")
-                            .expect("Failed to parse due date");
-                        let due_date = due_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
-                        server::create_task(task_name, due_date, id)
-                            .await
-                            .expect("Failed to create list");
-                        update_tasks().await;
+                    },
+                    for option_role in Role::ALL {
+                        option { value: option_role.as_str(), {option_role.as_str()} }
                     }
-                },
-                "Create"
+                }
+                " "
+                button {
+                    r#type: "submit",
+                    onclick: move |event| {
+                        event.prevent_default();
+                        async move {
+                            if server::create_invite(id, *invite_role.read()).await.is_err() {
+                                message.set("Failed to create invite".to_owned());
+                            }
+                            update_invites().await;
+                        }
+                    },
+                    "Create invite link"
+                }
             }
         }
     }
 }
 
+/// A task of the `Lists` page, followed by its subtasks.
 #[component]
-fn Login() -> Element {
-    let mut auth = use_context::<Signal<bool>>();
-
-    if auth.read().clone() {
-        navigator().push(Route::Home {});
-        return rsx! {};
-    }
+fn TaskItem(
+    node: TaskNode,
+    /// Every task of the list, to pick a new parent from.
+    tasks: Vec<(Uuid, String)>,
+    tz: Tz,
+    role: Role,
+    /// Tasks can only be dragged around in the manual order.
+    order: TaskOrder,
+    /// Task being dragged, shared by every item of the list.
+    dragged: Signal<Option<Uuid>>,
+    /// Every label of the user.
+    labels: Vec<Label>,
+    /// Labels of the user on the tasks of the list.
+    task_labels: Vec<TaskLabel>,
+    message: Signal<String>,
+    on_update: EventHandler,
+) -> Element {
+    let mut message = message;
+    let mut dragged = dragged;
+    let draggable = order == TaskOrder::Manual && role >= Role::Editor;
 
-    let mut username = use_signal(|| String::new());
-    let mut password = use_signal(|| String::new());
+    let task = node.task.clone();
+    let (done, total) = node.progress();
+    let subtree_ids = node.subtree_ids();
 
-    let mut message = use_signal(|| String::new());
+    let mut editing = use_signal(|| false);
+    let mut edit_name = use_signal(String::new);
+    let mut edit_due_date = use_signal(String::new);
+    let mut edit_due_time = use_signal(String::new);
+    let mut edit_recurrence = use_signal(String::new);
+    let mut edit_parent = use_signal(|| None::<Uuid>);
+    let mut edit_priority = use_signal(Priority::default);
 
-    rsx! {
-        if !message.read().is_empty() {
-            p { "{message}" }
-        }
-        form {
-            input {
-                r#type: "text",
-                placeholder: "Username",
-                value: username.read().clone(),
-                oninput: move |event| username.set(event.value()),
-            }
-            " "
-            input {
-                r#type: "password",
-                placeholder: "Password",
-                value: password.read().clone(),
-                oninput: move |event| password.set(event.value()),
-            }
-            " "
-            button {
-                r#type: "submit",
-                onclick: move |event| {
-                    event.prevent_default();
-                    async move {
-                        let username = username.read().clone();
-                        let password = password.read().clone();
-                        if let Err(_) = server::login(username, password).await {
-                            message.set("Login failed".to_owned());
-                        } else {
-                            auth.set(true);
-                            navigator().push(Route::Home {});
-                        }
-                    }
-                },
-                "Login"
-            }
-        }
-    }
-}
+    let mut adding_subtask = use_signal(|| false);
+    let mut subtask_name = use_signal(String::new);
 
-#[component]
-fn Register() -> Element {
-    let mut auth = use_context::<Signal<bool>>();
+    let mut history = use_signal(|| None::<Vec<Task>>);
 
-    if auth.read().clone() {
-        navigator().push(Route::Home {});
-        return rsx! {};
-    }
+    let task_id = task.id;
+    let list_id = task.list_id;
+    let parent_id = task.parent_id;
+    let auto_complete = task.auto_complete;
+    let toggled_task = task.clone();
+    let edited_task = task.clone();
+    let deleted_task = task.clone();
 
-    let mut username = use_signal(|| String::new());
-    let mut password = use_signal(|| String::new());
+    let (attached_labels, other_labels): (Vec<Label>, Vec<Label>) =
+        labels.iter().cloned().partition(|label| {
+            task_labels.contains(&TaskLabel {
+                task_id,
+                label_id: label.id,
+            })
+        });
 
-    let mut message = use_signal(|| String::new());
+    // A task can't move under itself or one of its subtasks.
+    let parent_options: Vec<(Uuid, String)> = tasks
+        .iter()
+        .filter(|(id, _)| !subtree_ids.contains(id))
+        .cloned()
+        .collect();
 
     rsx! {
-        if !message.read().is_empty() {
-            p { "{message}" }
-        }
-        form {
-            input {
-                r#type: "text",
-                placeholder: "Username",
-                value: username.read().clone(),
-                oninput: move |event| username.set(event.value()),
-            }
-            " "
-            input {
-                r#type: "password",
-                placeholder: "Password",
-                value: password.read().clone(),
-                oninput: move |event| password.set(event.value()),
-            }
-            " "
-            button {
-                r#type: "submit",
-                onclick: move |event| {
+        li {
+            draggable: draggable,
+            ondragstart: move |event| {
+                event.stop_propagation();
+                dragged.set(Some(task_id));
+            },
+            ondragend: move |_| dragged.set(None),
+            ondragover: move |event| {
+                if draggable {
                     event.prevent_default();
+                }
+            },
+            ondrop: move |event| {
+                event.prevent_default();
+                event.stop_propagation();
+                let dragged_id = dragged.take();
+                async move {
+                    let Some(dragged_id) = dragged_id.filter(|&id| id != task_id) else {
+                        return;
+                    };
+                    if server::reorder_task(dragged_id, task_id).await.is_err() {
+                        message.set("Tasks can only be reordered next to tasks of the same parent".to_owned());
+                    }
+                    on_update.call(());
+                }
+            },
+            input {
+                r#type: "checkbox",
+                checked: "{task.completed_at.is_some()}",
+                disabled: role < Role::Editor,
+                onchange: move |_| {
+                    let task_clone = toggled_task.clone();
                     async move {
-                        let username = username.read().clone();
-                        let password = password.read().clone();
-                        if let Err(_) = server::register(username, password).await {
-                            message.set("Registration failed".to_owned());
+                        if task_clone.completed_at.is_none() {
+                            server::complete_task(task_clone)
+                                .await
+                                .expect("Failed to complete task");
                         } else {
-                            auth.set(true);
-                            navigator().push(Route::Home {});
+                            server::uncomplete_task(task_clone)
+                                .await
+                                .expect("Failed to uncomplete task");
                         }
+                        on_update.call(());
                     }
                 },
-                "Register"
             }
+            " "
+            if *editing.read() {
+                form {
+                    input {
+                        r#type: "text",
+                        value: "{edit_name}",
+                        oninput: move |event| edit_name.set(event.value()),
+                    }
+                    " "
+                    input {
+                        r#type: "date",
+                        value: "{edit_due_date}",
+                        oninput: move |event| edit_due_date.set(event.value()),
+                    }
+                    " "
+                    input {
+                        r#type: "time",
+                        value: "{edit_due_time}",
+                        oninput: move |event| edit_due_time.set(event.value()),
+                    }
+                    " "
+                    input {
+                        r#type: "text",
+                        placeholder: "repeat, e.g. FREQ=WEEKLY;BYDAY=MO",
+                        value: "{edit_recurrence}",
+                        oninput: move |event| edit_recurrence.set(event.value()),
+                    }
+                    " "
+                    select {
+                        value: edit_priority.read().as_str(),
+                        onchange: move |event| {
+                            if let Ok(priority) = event.value().parse() {
+                                edit_priority.set(priority);
+                            }
+                        },
+                        for option_priority in Priority::ALL {
+                            option { value: option_priority.as_str(), {option_priority.as_str()} }
+                        }
+                    }
+                    " "
+                    select {
+                        value: edit_parent().map(|id| id.to_string()).unwrap_or_default(),
+                        onchange: move |event| edit_parent.set(event.value().parse().ok()),
+                        option { value: "", "No parent task" }
+                        for (id, title) in parent_options {
+                            option { value: "{id}", "{title}" }
+                        }
+                    }
+                    " "
+                    button {
+                        r#type: "submit",
+                        onclick: move |event| {
+                            event.prevent_default();
+                            async move {
+                                let title = edit_name.read().clone();
+                                if title.trim().is_empty() {
+                                    return;
+                                }
+                                let Some((due_date, due_time)) = parse_due_inputs(
+                                    &edit_due_date.read(),
+                                    &edit_due_time.read(),
+                                ) else {
+                                    return;
+                                };
+                                let recurrence = edit_recurrence.read().trim().to_owned();
+                                if let Err(error) = parse_recurrence_input(&recurrence) {
+                                    message.set(format!("Invalid repeat rule: {error}"));
+                                    return;
+                                }
+                                let recurrence = Some(recurrence).filter(|recurrence| !recurrence.is_empty());
+                                if server::update_task(
+                                        task_id,
+                                        title,
+                                        due_date,
+                                        due_time,
+                                        recurrence,
+                                        *edit_priority.read(),
+                                    )
+                                    .await
+                                    .is_err()
+                                {
+                                    message.set("Failed to update task".to_owned());
+                                }
+                                let new_parent_id = *edit_parent.read();
+                                if new_parent_id != parent_id
+                                    && server::move_task(task_id, new_parent_id).await.is_err()
+                                {
+                                    message.set("Failed to move task".to_owned());
+                                }
+                                editing.set(false);
+                                on_update.call(());
+                            }
+                        },
+                        "Save"
+                    }
+                    " "
+                    button {
+                        r#type: "button",
+                        onclick: move |_| editing.set(false),
+                        "Cancel"
+                    }
+                }
+            } else {
+                span { "{task.title}" }
+                if task.priority != Priority::None {
+                    " ["
+                    span { {task.priority.as_str()} }
+                    " priority]"
+                }
+                if total > 0 {
+                    " ("
+                    span { "{done}/{total} done" }
+                    ")"
+                }
+                for label in attached_labels {
+                    " "
+                    span { style: "border-left: 0.75em solid {label.color}; padding-left: 0.25em",
+                        Link { to: Route::LabelTasks { id: label.id }, "{label.name}" }
+                        " "
+                        button {
+                            onclick: move |_| async move {
+                                if server::detach_label(task_id, label.id).await.is_err() {
+                                    message.set("Failed to remove label".to_owned());
+                                }
+                                on_update.call(());
+                            },
+                            "×"
+                        }
+                    }
+                }
+                if !other_labels.is_empty() {
+                    " "
+                    select {
+                        value: "",
+                        onchange: move |event| async move {
+                            let Ok(label_id) = event.value().parse() else {
+                                return;
+                            };
+                            if server::attach_label(task_id, label_id).await.is_err() {
+                                message.set("Failed to add label".to_owned());
+                            }
+                            on_update.call(());
+                        },
+                        option { value: "", "Add label" }
+                        for label in other_labels {
+                            option { value: "{label.id}", "{label.name}" }
+                        }
+                    }
+                }
+                if role >= Role::Editor {
+                    " "
+                    button {
+                        onclick: move |_| {
+                            let (date, time) = edited_task.local_due(tz).unzip();
+                            edit_name.set(edited_task.title.clone());
+                            edit_due_date.set(
+                                date.map(|date| date.format("%Y-%m-%d").to_string()).unwrap_or_default(),
+                            );
+                            edit_due_time.set(
+                                time
+                                    .flatten()
+                                    .map(|time| time.format("%H:%M").to_string())
+                                    .unwrap_or_default(),
+                            );
+                            edit_recurrence.set(edited_task.recurrence.clone().unwrap_or_default());
+                            edit_parent.set(edited_task.parent_id);
+                            edit_priority.set(edited_task.priority);
+                            editing.set(true);
+                        },
+                        "Edit"
+                    }
+                    " "
+                    button {
+                        onclick: move |_| adding_subtask.set(true),
+                        "Add subtask"
+                    }
+                    " "
+                    button {
+                        onclick: move |_| {
+                            let task_clone = deleted_task.clone();
+                            async move {
+                                if server::delete_task(task_clone).await.is_err() {
+                                    message.set("Failed to delete task".to_owned());
+                                }
+                                on_update.call(());
+                            }
+                        },
+                        "Delete"
+                    }
+                }
+            }
+            if let Some((date, time)) = task.local_due(tz) {
+                div {
+                    "Due on: "
+                    span { {date.format("%Y-%m-%d").to_string()} }
+                    if let Some(time) = time {
+                        " at "
+                        span { {time.format("%H:%M").to_string()} }
+                    }
+                    if task.is_overdue(Utc::now(), tz) {
+                        " (overdue)"
+                    }
+                }
+            }
+            if let Some(rule) = task
+                .recurrence
+                .as_ref()
+                .and_then(|rule| rule.parse::<Recurrence>().ok())
+            {
+                div {
+                    "Repeats "
+                    span { {rule.describe()} }
+                    " "
+                    if history.read().is_some() {
+                        button { onclick: move |_| history.set(None), "Hide history" }
+                    } else {
+                        button {
+                            onclick: move |_| async move {
+                                match server::get_task_history(task_id).await {
+                                    Ok(occurrences) => history.set(Some(occurrences)),
+                                    Err(_) => message.set("Failed to get task history".to_owned()),
+                                }
+                            },
+                            "Show history"
+                        }
+                    }
+                }
+            }
+            if let Some(occurrences) = history.read().clone() {
+                ul {
+                    for occurrence in occurrences {
+                        li {
+                            span {
+                                {
+                                    occurrence
+                                        .local_due(tz)
+                                        .map(|(date, _)| date.format("%Y-%m-%d").to_string())
+                                        .unwrap_or_default()
+                                }
+                            }
+                            ": "
+                            if let Some(completed_at) = occurrence.completed_at {
+                                "completed at "
+                                span { {completed_at.with_timezone(&tz).format("%Y-%m-%d %H:%M").to_string()} }
+                                if let Some(completed_by) = occurrence.completed_by.as_ref() {
+                                    " by "
+                                    span { "{completed_by}" }
+                                }
+                            } else {
+                                "open"
+                            }
+                        }
+                    }
+                }
+            }
+            if let (Some(completed_at), Some(completed_by)) = (
+                task.completed_at,
+                task.completed_by.as_ref(),
+            )
+            {
+                div {
+                    "Completed at: "
+                    span { {completed_at.format("%Y-%m-%d %H:%M:%S").to_string()} }
+                    " by "
+                    span { {completed_by.to_string()} }
+                }
+            }
+            if total > 0 && role >= Role::Editor {
+                div {
+                    label {
+                        input {
+                            r#type: "checkbox",
+                            checked: "{auto_complete}",
+                            onchange: move |_| async move {
+                                if server::set_auto_complete(task_id, !auto_complete).await.is_err() {
+                                    message.set("Failed to update task".to_owned());
+                                }
+                                on_update.call(());
+                            },
+                        }
+                        " Complete when all subtasks are done"
+                    }
+                }
+            }
+            if *adding_subtask.read() {
+                form {
+                    input {
+                        r#type: "text",
+                        placeholder: "subtask name",
+                        value: "{subtask_name}",
+                        oninput: move |event| subtask_name.set(event.value()),
+                    }
+                    " "
+                    button {
+                        r#type: "submit",
+                        onclick: move |event| {
+                            event.prevent_default();
+                            async move {
+                                let title = subtask_name.read().clone();
+                                if title.trim().is_empty() {
+                                    return;
+                                }
+                                if server::create_task(
+                                        title,
+                                        None,
+                                        None,
+                                        None,
+                                        Priority::default(),
+                                        Some(task_id),
+                                        list_id,
+                                    )
+                                    .await
+                                    .is_err()
+                                {
+                                    message.set("Failed to create task".to_owned());
+                                }
+                                subtask_name.set(String::new());
+                                adding_subtask.set(false);
+                                on_update.call(());
+                            }
+                        },
+                        "Add"
+                    }
+                    " "
+                    button {
+                        r#type: "button",
+                        onclick: move |_| adding_subtask.set(false),
+                        "Cancel"
+                    }
+                }
+            }
+            if !node.children.is_empty() {
+                ul {
+                    for child in node.children.clone() {
+                        TaskItem {
+                            key: "{child.task.id}",
+                            node: child,
+                            tasks: tasks.clone(),
+                            tz,
+                            role,
+                            order,
+                            dragged,
+                            labels: labels.clone(),
+                            task_labels: task_labels.clone(),
+                            message,
+                            on_update,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn Labels() -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    }
+
+    let mut labels = use_signal(Vec::<Label>::new);
+
+    let mut label_name = use_signal(String::new);
+    let mut label_color = use_signal(|| "#888888".to_owned());
+
+    let mut editing = use_signal(|| None::<Uuid>);
+    let mut edit_name = use_signal(String::new);
+    let mut edit_color = use_signal(String::new);
+
+    let mut message = use_signal(String::new);
+
+    let update_labels = move || async move {
+        labels.set(server::get_labels().await.expect("Failed to get labels"));
+    };
+
+    use_future(update_labels);
+
+    rsx! {
+        h1 { "Labels" }
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        ul {
+            for label in labels.read().clone() {
+                li {
+                    if *editing.read() == Some(label.id) {
+                        form {
+                            input {
+                                r#type: "text",
+                                value: "{edit_name}",
+                                oninput: move |event| edit_name.set(event.value()),
+                            }
+                            " "
+                            input {
+                                r#type: "color",
+                                value: "{edit_color}",
+                                oninput: move |event| edit_color.set(event.value()),
+                            }
+                            " "
+                            button {
+                                r#type: "submit",
+                                onclick: move |event| {
+                                    event.prevent_default();
+                                    let id = label.id;
+                                    async move {
+                                        let name = edit_name.read().clone();
+                                        let color = edit_color.read().clone();
+                                        match server::update_label(id, name, color).await {
+                                            Ok(()) => {
+                                                message.set(String::new());
+                                                editing.set(None);
+                                            }
+                                            Err(_) => message.set("Failed to update label".to_owned()),
+                                        }
+                                        update_labels().await;
+                                    }
+                                },
+                                "Save"
+                            }
+                            " "
+                            button {
+                                r#type: "button",
+                                onclick: move |_| editing.set(None),
+                                "Cancel"
+                            }
+                        }
+                    } else {
+                        span { style: "border-left: 0.75em solid {label.color}; padding-left: 0.25em",
+                            Link { to: Route::LabelTasks { id: label.id }, "{label.name}" }
+                        }
+                        " "
+                        button {
+                            onclick: move |_| {
+                                let id = label.id;
+                                if let Some(label) = labels.read().iter().find(|label| label.id == id) {
+                                    edit_name.set(label.name.clone());
+                                    edit_color.set(label.color.clone());
+                                }
+                                editing.set(Some(id));
+                            },
+                            "Edit"
+                        }
+                        " "
+                        button {
+                            onclick: move |_| {
+                                let id = label.id;
+                                async move {
+                                    if server::delete_label(id).await.is_err() {
+                                        message.set("Failed to delete label".to_owned());
+                                    }
+                                    update_labels().await;
+                                }
+                            },
+                            "Delete"
+                        }
+                    }
+                }
+            }
+        }
+        form {
+            input {
+                r#type: "text",
+                placeholder: "label name, e.g. @office",
+                value: "{label_name}",
+                oninput: move |event| label_name.set(event.value()),
+            }
+            " "
+            input {
+                r#type: "color",
+                value: "{label_color}",
+                oninput: move |event| label_color.set(event.value()),
+            }
+            " "
+            button {
+                r#type: "submit",
+                onclick: move |event| {
+                    event.prevent_default();
+                    async move {
+                        let name = label_name.read().clone();
+                        if name.trim().is_empty() {
+                            return;
+                        }
+                        match server::create_label(name, label_color.read().clone()).await {
+                            Ok(()) => {
+                                message.set(String::new());
+                                label_name.set(String::new());
+                            }
+                            Err(_) => message.set("Failed to create label, is the name already used?".to_owned()),
+                        }
+                        update_labels().await;
+                    }
+                },
+                "Create"
+            }
+        }
+    }
+}
+
+/// Tasks carrying a label, from every list, grouped by list.
+#[component]
+fn LabelTasks(id: Uuid) -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    }
+
+    let label = use_resource(move || async move { server::get_label(id).await.ok() });
+    let mut tasks =
+        use_resource(move || async move { server::get_label_tasks(id).await.unwrap_or_default() });
+
+    // Still loading.
+    let Some(label) = label.read().clone() else {
+        return rsx! {};
+    };
+    let Some(label) = label else {
+        return rsx! {
+            p { "Label not found." }
+        };
+    };
+
+    rsx! {
+        h1 {
+            span { style: "border-left: 0.75em solid {label.color}; padding-left: 0.25em",
+                "{label.name}"
+            }
+        }
+        GroupedTasks {
+            tasks: tasks.read().clone().unwrap_or_default(),
+            empty_message: "No task has this label.",
+            on_update: move |_| tasks.restart(),
+        }
+    }
+}
+
+#[component]
+fn Filters() -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    }
+
+    let mut saved_filters = use_context::<Signal<Vec<SavedFilter>>>();
+
+    let mut filter_name = use_signal(String::new);
+    let mut filter_query = use_signal(String::new);
+
+    let mut editing = use_signal(|| None::<Uuid>);
+    let mut edit_name = use_signal(String::new);
+    let mut edit_query = use_signal(String::new);
+
+    let mut message = use_signal(String::new);
+
+    let update_saved_filters = move || async move {
+        saved_filters.set(
+            server::get_saved_filters()
+                .await
+                .expect("Failed to get saved filters"),
+        );
+    };
+
+    // Checked as the query is typed, the server checking it again on save.
+    let query_error = |query: &str| match query.trim() {
+        "" => None,
+        query => query.parse::<Filter>().err().map(|error| error.to_string()),
+    };
+    let filter_error = query_error(&filter_query.read());
+    let edit_error = query_error(&edit_query.read());
+
+    rsx! {
+        h1 { "Filters" }
+        p {
+            "Filters gather tasks from every list, such as "
+            code { "due:<7d label:work !completed list:\"Sprint 12\"" }
+            ". Terms are "
+            code { "due:" }
+            " with today, tomorrow, a date or a number of days or weeks such as 7d or 2w, "
+            code { "label:" }
+            ", "
+            code { "list:" }
+            ", "
+            code { "priority:" }
+            ", "
+            code { "completed" }
+            ", "
+            code { "overdue" }
+            " and words of the title. "
+            code { "<" }
+            ", "
+            code { "<=" }
+            ", "
+            code { ">" }
+            " and "
+            code { ">=" }
+            " compare due dates and priorities, "
+            code { "!" }
+            " negates a term and double quotes keep spaces in a value."
+        }
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        ul {
+            for saved_filter in saved_filters.read().clone() {
+                li {
+                    if *editing.read() == Some(saved_filter.id) {
+                        form {
+                            input {
+                                r#type: "text",
+                                value: "{edit_name}",
+                                oninput: move |event| edit_name.set(event.value()),
+                            }
+                            " "
+                            input {
+                                r#type: "text",
+                                value: "{edit_query}",
+                                oninput: move |event| edit_query.set(event.value()),
+                            }
+                            " "
+                            button {
+                                r#type: "submit",
+                                disabled: edit_error.is_some(),
+                                onclick: move |event| {
+                                    event.prevent_default();
+                                    let id = saved_filter.id;
+                                    async move {
+                                        let name = edit_name.read().clone();
+                                        let query = edit_query.read().clone();
+                                        match server::update_saved_filter(id, name, query).await {
+                                            Ok(()) => {
+                                                message.set(String::new());
+                                                editing.set(None);
+                                            }
+                                            Err(_) => message.set("Failed to update filter".to_owned()),
+                                        }
+                                        update_saved_filters().await;
+                                    }
+                                },
+                                "Save"
+                            }
+                            " "
+                            button {
+                                r#type: "button",
+                                onclick: move |_| editing.set(None),
+                                "Cancel"
+                            }
+                            if let Some(error) = edit_error.clone() {
+                                p { "{error}" }
+                            }
+                        }
+                    } else {
+                        Link { to: Route::FilterTasks { id: saved_filter.id }, "{saved_filter.name}" }
+                        ": "
+                        code { "{saved_filter.query}" }
+                        " "
+                        button {
+                            onclick: move |_| {
+                                let id = saved_filter.id;
+                                if let Some(saved_filter) = saved_filters
+                                    .read()
+                                    .iter()
+                                    .find(|saved_filter| saved_filter.id == id)
+                                {
+                                    edit_name.set(saved_filter.name.clone());
+                                    edit_query.set(saved_filter.query.clone());
+                                }
+                                editing.set(Some(id));
+                            },
+                            "Edit"
+                        }
+                        " "
+                        button {
+                            onclick: move |_| {
+                                let id = saved_filter.id;
+                                async move {
+                                    if server::delete_saved_filter(id).await.is_err() {
+                                        message.set("Failed to delete filter".to_owned());
+                                    }
+                                    update_saved_filters().await;
+                                }
+                            },
+                            "Delete"
+                        }
+                    }
+                }
+            }
+        }
+        form {
+            input {
+                r#type: "text",
+                placeholder: "filter name",
+                value: "{filter_name}",
+                oninput: move |event| filter_name.set(event.value()),
+            }
+            " "
+            input {
+                r#type: "text",
+                placeholder: "due:<7d !completed",
+                value: "{filter_query}",
+                oninput: move |event| filter_query.set(event.value()),
+            }
+            " "
+            button {
+                r#type: "submit",
+                disabled: filter_error.is_some(),
+                onclick: move |event| {
+                    event.prevent_default();
+                    async move {
+                        let name = filter_name.read().clone();
+                        let query = filter_query.read().clone();
+                        if name.trim().is_empty() || query.trim().is_empty() {
+                            return;
+                        }
+                        match server::create_saved_filter(name, query).await {
+                            Ok(()) => {
+                                message.set(String::new());
+                                filter_name.set(String::new());
+                                filter_query.set(String::new());
+                            }
+                            Err(_) => message.set("Failed to create filter, is the name already used?".to_owned()),
+                        }
+                        update_saved_filters().await;
+                    }
+                },
+                "Create"
+            }
+            if let Some(error) = filter_error.clone() {
+                p { "{error}" }
+            }
+        }
+    }
+}
+
+/// Tasks matching a saved filter, grouped by list.
+#[component]
+fn FilterTasks(id: Uuid) -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    }
+
+    let saved_filter = use_resource(move || async move { server::get_saved_filter(id).await.ok() });
+    let mut tasks = use_resource(move || async move {
+        server::get_saved_filter_tasks(id).await.unwrap_or_default()
+    });
+
+    // Still loading.
+    let Some(saved_filter) = saved_filter.read().clone() else {
+        return rsx! {};
+    };
+    let Some(saved_filter) = saved_filter else {
+        return rsx! {
+            p { "Filter not found." }
+        };
+    };
+
+    rsx! {
+        h1 { "{saved_filter.name}" }
+        p {
+            code { "{saved_filter.query}" }
+        }
+        GroupedTasks {
+            tasks: tasks.read().clone().unwrap_or_default(),
+            empty_message: "No task matches this filter.",
+            on_update: move |_| tasks.restart(),
+        }
+    }
+}
+
+/// Tasks and lists whose title matches the query of the search box.
+#[component]
+fn Search(query: String) -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    }
+
+    let results = use_resource(use_reactive!(|query| async move {
+        server::search(query).await.unwrap_or_default()
+    }));
+
+    let Some(results) = results.read().clone() else {
+        return rsx! {
+            h1 { "Search" }
+        };
+    };
+
+    rsx! {
+        h1 { "Search" }
+        if results.is_empty() {
+            p { "Nothing matches “{query}”." }
+        }
+        ul {
+            for result in results {
+                li {
+                    Link { to: Route::Lists { id: result.list_id },
+                        for (part, highlighted) in result.snippet_parts() {
+                            if highlighted {
+                                mark { "{part}" }
+                            } else {
+                                "{part}"
+                            }
+                        }
+                    }
+                    if result.task_id.is_some() {
+                        " in "
+                        span { "{result.list_title}" }
+                    } else {
+                        " (list)"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn Today() -> Element {
+    rsx! {
+        SmartViewPage { view: SmartView::Today }
+    }
+}
+
+#[component]
+fn Upcoming() -> Element {
+    rsx! {
+        SmartViewPage { view: SmartView::Upcoming }
+    }
+}
+
+#[component]
+fn Overdue() -> Element {
+    rsx! {
+        SmartViewPage { view: SmartView::Overdue }
+    }
+}
+
+#[component]
+fn Completed() -> Element {
+    rsx! {
+        SmartViewPage { view: SmartView::Completed }
+    }
+}
+
+/// Tasks of every list of the user in one of the built-in views.
+#[component]
+fn SmartViewPage(view: SmartView) -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    }
+
+    let mut tasks =
+        use_resource(move || async move { server::get_smart_view(view).await.unwrap_or_default() });
+
+    rsx! {
+        h1 { {view.title()} }
+        GroupedTasks {
+            tasks: tasks.read().clone().unwrap_or_default(),
+            empty_message: view.empty_message(),
+            on_update: move |_| tasks.restart(),
+        }
+    }
+}
+
+/// Tasks from several lists, under the title of their list.
+#[component]
+fn GroupedTasks(tasks: Vec<Task>, empty_message: &'static str, on_update: EventHandler) -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+    let tz = auth.read().as_ref().map(User::tz).unwrap_or(Tz::UTC);
+
+    let lists = use_resource(|| async { server::get_lists().await.unwrap_or_default() });
+
+    let groups: Vec<(List, Vec<Task>)> = lists
+        .read()
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|list| {
+            let list_tasks: Vec<Task> = tasks
+                .iter()
+                .filter(|task| task.list_id == list.id)
+                .cloned()
+                .collect();
+            (list, list_tasks)
+        })
+        .filter(|(_, list_tasks)| !list_tasks.is_empty())
+        .collect();
+
+    rsx! {
+        if groups.is_empty() {
+            p { "{empty_message}" }
+        }
+        for (list, list_tasks) in groups {
+            h2 {
+                Link { to: Route::Lists { id: list.id }, "{list.title}" }
+            }
+            ul {
+                for task in list_tasks {
+                    GroupedTask {
+                        key: "{task.id}",
+                        task,
+                        role: list.role,
+                        tz,
+                        on_update,
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn GroupedTask(task: Task, role: Role, tz: Tz, on_update: EventHandler) -> Element {
+    let toggled_task = task.clone();
+
+    rsx! {
+        li {
+            input {
+                r#type: "checkbox",
+                checked: "{task.completed_at.is_some()}",
+                disabled: role < Role::Editor,
+                onchange: move |_| {
+                    let task_clone = toggled_task.clone();
+                    async move {
+                        if task_clone.completed_at.is_none() {
+                            server::complete_task(task_clone)
+                                .await
+                                .expect("Failed to complete task");
+                        } else {
+                            server::uncomplete_task(task_clone)
+                                .await
+                                .expect("Failed to uncomplete task");
+                        }
+                        on_update.call(());
+                    }
+                },
+            }
+            " "
+            span { "{task.title}" }
+            if let Some((date, time)) = task.local_due(tz) {
+                ", due on "
+                span { {date.format("%Y-%m-%d").to_string()} }
+                if let Some(time) = time {
+                    " at "
+                    span { {time.format("%H:%M").to_string()} }
+                }
+                if task.is_overdue(Utc::now(), tz) {
+                    " (overdue)"
+                }
+            }
+            if let Some(completed_at) = task.completed_at {
+                ", completed at "
+                span { {completed_at.with_timezone(&tz).format("%Y-%m-%d %H:%M").to_string()} }
+            }
+        }
+    }
+}
+
+#[component]
+fn Trash() -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    }
+
+    let mut lists = use_signal(Vec::<List>::new);
+
+    let mut message = use_signal(String::new);
+
+    let update_lists = move || async move {
+        lists.set(
+            server::get_deleted_lists()
+                .await
+                .expect("Failed to get deleted lists"),
+        );
+    };
+
+    use_future(update_lists);
+
+    rsx! {
+        h1 { "Trash" }
+        p { "Deleted lists are purged for good after a while, along with their tasks." }
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        if lists.read().is_empty() {
+            p { "The trash is empty." }
+        }
+        ul {
+            for list in lists.read().clone() {
+                li {
+                    span { "{list.title}" }
+                    ", deleted "
+                    span {
+                        {
+                            list.deleted_at
+                                .map(|deleted_at| deleted_at.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_default()
+                        }
+                    }
+                    " "
+                    button {
+                        onclick: move |_| {
+                            let id = list.id;
+                            async move {
+                                if server::restore_list(id).await.is_err() {
+                                    message.set("Failed to restore list".to_owned());
+                                }
+                                update_lists().await;
+                            }
+                        },
+                        "Restore"
+                    }
+                    " "
+                    button {
+                        onclick: move |_| {
+                            let id = list.id;
+                            async move {
+                                if server::purge_list(id).await.is_err() {
+                                    message.set("Failed to delete list".to_owned());
+                                }
+                                update_lists().await;
+                            }
+                        },
+                        "Delete forever"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn Invitation(token: String) -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        return rsx! {
+            div {
+                h1 { "You have been invited to a list!" }
+                p { "Please log in or register, then open this link again." }
+            }
+        };
+    }
+
+    let mut message = use_signal(String::new);
+
+    rsx! {
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        button {
+            onclick: move |_| {
+                let token = token.clone();
+                async move {
+                    match server::redeem_invite(token).await {
+                        Ok(id) => {
+                            navigator().push(Route::Lists { id });
+                        }
+                        Err(_) => message.set("This invite is invalid or has expired".to_owned()),
+                    }
+                }
+            },
+            "Join list"
+        }
+    }
+}
+
+#[component]
+fn Login() -> Element {
+    let mut auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_some() {
+        navigator().push(Route::Home {});
+        return rsx! {};
+    }
+
+    let mut username = use_signal(String::new);
+    let mut password = use_signal(String::new);
+    let mut code = use_signal(String::new);
+    let mut totp_required = use_signal(|| false);
+
+    let oidc_enabled = use_resource(server::oidc_enabled);
+    let password_reset_enabled = use_resource(server::password_reset_enabled);
+
+    let mut message = use_signal(String::new);
+
+    rsx! {
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        if *totp_required.read() {
+            form {
+                input {
+                    r#type: "text",
+                    placeholder: "Authentication or recovery code",
+                    autocomplete: "one-time-code",
+                    value: code.read().clone(),
+                    oninput: move |event| code.set(event.value()),
+                }
+                " "
+                button {
+                    r#type: "submit",
+                    onclick: move |event| {
+                        event.prevent_default();
+                        async move {
+                            let code = code.read().clone();
+                            match server::login_totp(code).await {
+                                Ok(user) => {
+                                    auth.set(Some(user));
+                                    navigator().push(Route::Home {});
+                                }
+                                Err(_) => message.set("Invalid code".to_owned()),
+                            }
+                        }
+                    },
+                    "Verify"
+                }
+            }
+        } else {
+            form {
+                input {
+                    r#type: "text",
+                    placeholder: "Username",
+                    value: username.read().clone(),
+                    oninput: move |event| username.set(event.value()),
+                }
+                " "
+                input {
+                    r#type: "password",
+                    placeholder: "Password",
+                    value: password.read().clone(),
+                    oninput: move |event| password.set(event.value()),
+                }
+                " "
+                button {
+                    r#type: "submit",
+                    onclick: move |event| {
+                        event.prevent_default();
+                        async move {
+                            let username = username.read().clone();
+                            let password = password.read().clone();
+                            match server::login(username, password).await {
+                                Ok(Some(user)) => {
+                                    auth.set(Some(user));
+                                    navigator().push(Route::Home {});
+                                }
+                                Ok(None) => {
+                                    message.set(String::new());
+                                    totp_required.set(true);
+                                }
+                                Err(_) => message.set("Login failed".to_owned()),
+                            }
+                        }
+                    },
+                    "Login"
+                }
+            }
+            if matches!(*oidc_enabled.read(), Some(Ok(true))) {
+                p {
+                    button {
+                        onclick: move |_| async move {
+                            match server::oidc_login_url().await {
+                                Ok(url) => {
+                                    navigator().push(NavigationTarget::<Route>::External(url));
+                                }
+                                Err(_) => message.set("Single sign-on is unavailable".to_owned()),
+                            }
+                        },
+                        "Log in with single sign-on"
+                    }
+                }
+            }
+            if matches!(*password_reset_enabled.read(), Some(Ok(true))) {
+                p {
+                    Link { to: Route::ForgotPassword {}, "Forgot your password?" }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn OidcCallback(code: String, state: String) -> Element {
+    let mut auth = use_context::<Signal<Option<User>>>();
+
+    let mut message = use_signal(String::new);
+
+    use_future(move || {
+        let code = code.clone();
+        let state = state.clone();
+        async move {
+            match server::oidc_callback(code, state).await {
+                Ok(user) => {
+                    auth.set(Some(user));
+                    navigator().push(Route::Home {});
+                }
+                Err(_) => message.set("Single sign-on failed".to_owned()),
+            }
+        }
+    });
+
+    rsx! {
+        if message.read().is_empty() {
+            p { "Logging in..." }
+        } else {
+            p { "{message}" }
+            Link { to: Route::Login {}, "Back to login" }
+        }
+    }
+}
+
+#[component]
+fn Register() -> Element {
+    let mut auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_some() {
+        navigator().push(Route::Home {});
+        return rsx! {};
+    }
+
+    let mut username = use_signal(String::new);
+    let mut password = use_signal(String::new);
+
+    let mut message = use_signal(String::new);
+    let mut errors = use_signal(FieldErrors::default);
+
+    rsx! {
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        form {
+            input {
+                r#type: "text",
+                placeholder: "Username",
+                value: username.read().clone(),
+                oninput: move |event| username.set(event.value()),
+            }
+            for error in errors.read().get(Field::Username) {
+                p { "{error}" }
+            }
+            " "
+            input {
+                r#type: "password",
+                placeholder: "Password",
+                value: password.read().clone(),
+                oninput: move |event| password.set(event.value()),
+            }
+            for error in errors.read().get(Field::Password) {
+                p { "{error}" }
+            }
+            " "
+            button {
+                r#type: "submit",
+                onclick: move |event| {
+                    event.prevent_default();
+                    async move {
+                        let username = username.read().clone();
+                        let password = password.read().clone();
+                        if let Err(field_errors) = validate_registration(&username, &password) {
+                            errors.set(field_errors);
+                            return;
+                        }
+                        match server::register(username, password).await {
+                            Ok(user) => {
+                                auth.set(Some(user));
+                                navigator().push(Route::Home {});
+                            }
+                            Err(error) => {
+                                let field_errors = match error {
+                                    ServerFnError::ServerError(error) => {
+                                        error.parse().unwrap_or_default()
+                                    }
+                                    _ => FieldErrors::default(),
+                                };
+                                if field_errors.is_empty() {
+                                    message.set("Registration failed".to_owned());
+                                } else {
+                                    message.set(String::new());
+                                }
+                                errors.set(field_errors);
+                            }
+                        }
+                    }
+                },
+                "Register"
+            }
+        }
+    }
+}
+
+#[component]
+fn ForgotPassword() -> Element {
+    let auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_some() {
+        navigator().push(Route::Home {});
+        return rsx! {};
+    }
+
+    let mut login = use_signal(String::new);
+    let mut sent = use_signal(|| false);
+
+    let mut message = use_signal(String::new);
+
+    rsx! {
+        h1 { "Reset your password" }
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        if *sent.read() {
+            p {
+                "If an account with an email address matches, a link to reset its password "
+                "is on its way. It expires in an hour."
+            }
+        } else {
+            form {
+                input {
+                    r#type: "text",
+                    placeholder: "Username or email",
+                    value: login.read().clone(),
+                    oninput: move |event| login.set(event.value()),
+                }
+                " "
+                button {
+                    r#type: "submit",
+                    onclick: move |event| {
+                        event.prevent_default();
+                        async move {
+                            let login = login.read().clone();
+                            if login.is_empty() {
+                                return;
+                            }
+                            if server::request_password_reset(login).await.is_err() {
+                                message.set("Failed to request a password reset".to_owned());
+                            } else {
+                                message.set(String::new());
+                                sent.set(true);
+                            }
+                        }
+                    },
+                    "Send reset link"
+                }
+            }
+        }
+        Link { to: Route::Login {}, "Back to login" }
+    }
+}
+
+#[component]
+fn ConfirmEmail(token: String) -> Element {
+    let mut auth = use_context::<Signal<Option<User>>>();
+
+    let mut message = use_signal(String::new);
+
+    use_future(move || {
+        let token = token.clone();
+        async move {
+            match server::confirm_email(token).await {
+                Ok(()) => {
+                    if let Ok(Some(user)) = server::current_user().await {
+                        auth.set(Some(user));
+                    }
+                    message.set("Your email address is confirmed".to_owned());
+                }
+                Err(_) => message.set("This link is invalid or has expired".to_owned()),
+            }
+        }
+    });
+
+    rsx! {
+        h1 { "Confirm email" }
+        if message.read().is_empty() {
+            p { "Confirming..." }
+        } else {
+            p { "{message}" }
+            Link { to: Route::Home {}, "Back to lists" }
+        }
+    }
+}
+
+#[component]
+fn ResetPassword(token: String) -> Element {
+    let mut password = use_signal(String::new);
+
+    let mut message = use_signal(String::new);
+    let mut errors = use_signal(FieldErrors::default);
+
+    rsx! {
+        h1 { "Choose a new password" }
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        form {
+            input {
+                r#type: "password",
+                placeholder: "New password",
+                value: password.read().clone(),
+                oninput: move |event| password.set(event.value()),
+            }
+            for error in errors.read().get(Field::Password) {
+                p { "{error}" }
+            }
+            " "
+            button {
+                r#type: "submit",
+                onclick: move |event| {
+                    event.prevent_default();
+                    let token = token.clone();
+                    async move {
+                        let password = password.read().clone();
+                        match server::reset_password(token, password).await {
+                            Ok(()) => {
+                                navigator().push(Route::Login {});
+                            }
+                            Err(error) => {
+                                let field_errors = match error {
+                                    ServerFnError::ServerError(error) => {
+                                        error.parse().unwrap_or_default()
+                                    }
+                                    _ => FieldErrors::default(),
+                                };
+                                if field_errors.is_empty() {
+                                    message.set("This link is invalid or has expired".to_owned());
+                                } else {
+                                    message.set(String::new());
+                                }
+                                errors.set(field_errors);
+                            }
+                        }
+                    }
+                },
+                "Reset password"
+            }
+        }
+    }
+}
+
+#[component]
+fn Settings() -> Element {
+    let mut auth = use_context::<Signal<Option<User>>>();
+
+    let Some(user) = auth.read().clone() else {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    };
+
+    let mut current_password = use_signal(String::new);
+    let mut new_password = use_signal(String::new);
+
+    let mut email = use_signal(|| user.email.clone().unwrap_or_default());
+    let mut email_errors = use_signal(FieldErrors::default);
+
+    let mut totp_status = use_resource(|| async { server::totp_enabled().await.ok() });
+    let totp_enabled = totp_status().flatten().unwrap_or(false);
+    let mut enrollment = use_signal(|| None::<TotpEnrollment>);
+    let mut totp_code = use_signal(String::new);
+    let mut totp_password = use_signal(String::new);
+    let mut recovery_codes = use_signal(Vec::<String>::new);
+
+    let oidc_enabled = use_resource(server::oidc_enabled);
+
+    let mut api_tokens = use_signal(Vec::<ApiToken>::new);
+    let mut token_name = use_signal(String::new);
+    let mut token_scope = use_signal(|| Scope::Read);
+    let mut new_token = use_signal(|| None::<String>);
+
+    let update_api_tokens = move || async move {
+        api_tokens.set(
+            server::get_api_tokens()
+                .await
+                .expect("Failed to get API tokens"),
+        );
+    };
+
+    use_future(update_api_tokens);
+
+    let mut message = use_signal(String::new);
+
+    rsx! {
+        h2 { "Change password" }
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        form {
+            input {
+                r#type: "password",
+                placeholder: "Current password",
+                value: current_password.read().clone(),
+                oninput: move |event| current_password.set(event.value()),
+            }
+            " "
+            input {
+                r#type: "password",
+                placeholder: "New password",
+                value: new_password.read().clone(),
+                oninput: move |event| new_password.set(event.value()),
+            }
+            " "
+            button {
+                r#type: "submit",
+                onclick: move |event| {
+                    event.prevent_default();
+                    async move {
+                        let current = current_password.read().clone();
+                        let new = new_password.read().clone();
+                        if server::change_password(current, new).await.is_err() {
+                            message.set("Failed to change password".to_owned());
+                        } else {
+                            current_password.set(String::new());
+                            new_password.set(String::new());
+                            message.set("Password changed, other sessions were logged out".to_owned());
+                        }
+                    }
+                },
+                "Change password"
+            }
+        }
+        h2 { "Email" }
+        p {
+            "Used to send you a link to reset your password if you forget it. "
+            "A new address is only saved once you open the link mailed to it."
+        }
+        form {
+            input {
+                r#type: "email",
+                placeholder: "Email",
+                value: email.read().clone(),
+                oninput: move |event| email.set(event.value()),
+            }
+            for error in email_errors.read().get(Field::Email) {
+                p { "{error}" }
+            }
+            " "
+            button {
+                r#type: "submit",
+                onclick: move |event| {
+                    event.prevent_default();
+                    async move {
+                        let new_email = email.read().clone();
+                        match server::set_email(new_email.clone()).await {
+                            Ok(user) => {
+                                let new_email = normalize_email(&new_email);
+                                if new_email.is_empty() || user.email.as_ref() == Some(&new_email) {
+                                    email.set(user.email.clone().unwrap_or_default());
+                                } else {
+                                    message.set(format!("A confirmation link was sent to {new_email}"));
+                                }
+                                email_errors.set(FieldErrors::default());
+                                auth.set(Some(user));
+                            }
+                            Err(error) => {
+                                let field_errors = match error {
+                                    ServerFnError::ServerError(error) => {
+                                        error.parse().unwrap_or_default()
+                                    }
+                                    _ => FieldErrors::default(),
+                                };
+                                if field_errors.is_empty() {
+                                    message.set("Failed to change email".to_owned());
+                                }
+                                email_errors.set(field_errors);
+                            }
+                        }
+                    }
+                },
+                "Save email"
+            }
+        }
+        h2 { "Time zone" }
+        p { "Due dates and times are shown in this time zone." }
+        select {
+            value: user.time_zone.clone(),
+            onchange: move |event| async move {
+                match server::set_time_zone(event.value()).await {
+                    Ok(user) => auth.set(Some(user)),
+                    Err(_) => message.set("Failed to change time zone".to_owned()),
+                }
+            },
+            for tz in TZ_VARIANTS {
+                option { value: tz.name(), {tz.name()} }
+            }
+        }
+        h2 { "Two-factor authentication" }
+        if totp_enabled {
+            p { "Two-factor authentication is enabled." }
+            if !recovery_codes.read().is_empty() {
+                p { "Save these recovery codes, each can be used once if you lose your device:" }
+                ul {
+                    for recovery_code in recovery_codes.read().clone() {
+                        li { code { "{recovery_code}" } }
+                    }
+                }
+            }
+            form {
+                input {
+                    r#type: "password",
+                    placeholder: "Password",
+                    value: totp_password.read().clone(),
+                    oninput: move |event| totp_password.set(event.value()),
+                }
+                " "
+                button {
+                    r#type: "submit",
+                    onclick: move |event| {
+                        event.prevent_default();
+                        async move {
+                            let password = totp_password.read().clone();
+                            if server::disable_totp(password).await.is_err() {
+                                message.set("Failed to disable two-factor authentication".to_owned());
+                            } else {
+                                totp_password.set(String::new());
+                                recovery_codes.set(Vec::new());
+                                totp_status.restart();
+                            }
+                        }
+                    },
+                    "Disable"
+                }
+            }
+        } else if let Some(pending) = enrollment.read().clone() {
+            p { "Scan this QR code with your authenticator app, then enter the code it shows." }
+            div { dangerous_inner_html: pending.qr_code }
+            p {
+                "Or enter the secret manually: "
+                code { "{pending.secret}" }
+            }
+            form {
+                input {
+                    r#type: "text",
+                    placeholder: "Code",
+                    autocomplete: "one-time-code",
+                    value: totp_code.read().clone(),
+                    oninput: move |event| totp_code.set(event.value()),
+                }
+                " "
+                button {
+                    r#type: "submit",
+                    onclick: move |event| {
+                        event.prevent_default();
+                        async move {
+                            let code = totp_code.read().clone();
+                            match server::confirm_totp_enrollment(code).await {
+                                Ok(codes) => {
+                                    recovery_codes.set(codes);
+                                    totp_code.set(String::new());
+                                    enrollment.set(None);
+                                    totp_status.restart();
+                                }
+                                Err(_) => message.set("Invalid code".to_owned()),
+                            }
+                        }
+                    },
+                    "Enable"
+                }
+            }
+        } else {
+            button {
+                onclick: move |_| async move {
+                    match server::start_totp_enrollment().await {
+                        Ok(new_enrollment) => enrollment.set(Some(new_enrollment)),
+                        Err(_) => message.set("Failed to start enrollment".to_owned()),
+                    }
+                },
+                "Set up two-factor authentication"
+            }
+        }
+        if matches!(*oidc_enabled.read(), Some(Ok(true))) {
+            h2 { "Single sign-on" }
+            button {
+                onclick: move |_| async move {
+                    match server::oidc_login_url().await {
+                        Ok(url) => {
+                            navigator().push(NavigationTarget::<Route>::External(url));
+                        }
+                        Err(_) => message.set("Single sign-on is unavailable".to_owned()),
+                    }
+                },
+                "Link a single sign-on account"
+            }
+        }
+        h2 { "API tokens" }
+        if let Some(token) = new_token.read().clone() {
+            p {
+                "Copy your new token now, it won't be shown again: "
+                code { "{token}" }
+            }
+        }
+        ul {
+            for api_token in api_tokens.read().clone() {
+                li {
+                    span { "{api_token.name}" }
+                    " ("
+                    span { {api_token.scope.as_str()} }
+                    "), created "
+                    span { {api_token.created_at.format("%Y-%m-%d").to_string()} }
+                    ", last used "
+                    span {
+                        {
+                            api_token
+                                .last_used_at
+                                .map(|last_used_at| last_used_at.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_else(|| "never".to_owned())
+                        }
+                    }
+                    " "
+                    button {
+                        onclick: move |_| {
+                            let api_token_clone = api_token.clone();
+                            async move {
+                                if server::revoke_api_token(api_token_clone).await.is_err() {
+                                    message.set("Failed to revoke token".to_owned());
+                                }
+                                update_api_tokens().await;
+                            }
+                        },
+                        "Revoke"
+                    }
+                }
+            }
+        }
+        form {
+            input {
+                r#type: "text",
+                placeholder: "token name",
+                value: "{token_name}",
+                oninput: move |event| token_name.set(event.value()),
+            }
+            " "
+            select {
+                value: token_scope.read().as_str(),
+                onchange: move |event| {
+                    if let Ok(scope) = event.value().parse() {
+                        token_scope.set(scope);
+                    }
+                },
+                for option_scope in Scope::ALL {
+                    option { value: option_scope.as_str(), {option_scope.as_str()} }
+                }
+            }
+            " "
+            button {
+                r#type: "submit",
+                onclick: move |event| {
+                    event.prevent_default();
+                    async move {
+                        let name = token_name.read().clone();
+                        if name.is_empty() {
+                            return;
+                        }
+                        match server::create_api_token(name, *token_scope.read()).await {
+                            Ok(token) => {
+                                new_token.set(Some(token));
+                                token_name.set(String::new());
+                            }
+                            Err(_) => message.set("Failed to create token".to_owned()),
+                        }
+                        update_api_tokens().await;
+                    }
+                },
+                "Create token"
+            }
+        }
+        h2 { "Sessions" }
+        Link { to: Route::Sessions {}, "Manage active sessions" }
+        h2 { "Delete account" }
+        Link { to: Route::DeleteAccount {}, "Delete my account" }
+    }
+}
+
+#[component]
+fn Sessions() -> Element {
+    let mut auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    }
+
+    let mut sessions = use_signal(Vec::<ActiveSession>::new);
+
+    let mut message = use_signal(String::new);
+
+    let update_sessions = move || async move {
+        sessions.set(
+            server::get_sessions()
+                .await
+                .expect("Failed to get sessions"),
+        );
+    };
+
+    use_future(update_sessions);
+
+    rsx! {
+        h1 { "Active sessions" }
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        ul {
+            for active_session in sessions.read().clone() {
+                li {
+                    span { {active_session.user_agent.clone().unwrap_or_else(|| "Unknown client".to_owned())} }
+                    if let Some(ip) = active_session.ip.as_ref() {
+                        " from "
+                        span { "{ip}" }
+                    }
+                    if active_session.current {
+                        " (this session)"
+                    }
+                    div {
+                        "Logged in at: "
+                        span { {active_session.created_at.format("%Y-%m-%d %H:%M").to_string()} }
+                        ", last seen at: "
+                        span { {active_session.last_seen_at.format("%Y-%m-%d %H:%M").to_string()} }
+                    }
+                    if !active_session.current {
+                        button {
+                            onclick: move |_| {
+                                let id = active_session.id;
+                                async move {
+                                    if server::revoke_session(id).await.is_err() {
+                                        message.set("Failed to revoke session".to_owned());
+                                    }
+                                    update_sessions().await;
+                                }
+                            },
+                            "Revoke"
+                        }
+                    }
+                }
+            }
+        }
+        button {
+            onclick: move |_| async move {
+                if server::logout_everywhere().await.is_err() {
+                    message.set("Failed to log out everywhere".to_owned());
+                } else {
+                    auth.set(None);
+                    navigator().push(Route::Home {});
+                }
+            },
+            "Log out everywhere"
+        }
+    }
+}
+
+#[component]
+fn DeleteAccount() -> Element {
+    let mut auth = use_context::<Signal<Option<User>>>();
+
+    if auth.read().is_none() {
+        navigator().push(Route::Login {});
+        return rsx! {};
+    }
+
+    let mut password = use_signal(String::new);
+
+    let mut message = use_signal(String::new);
+
+    rsx! {
+        h1 { "Delete your account?" }
+        p {
+            "Lists you share are handed over to their members, "
+            "the others are deleted along with their tasks. This cannot be undone."
+        }
+        if !message.read().is_empty() {
+            p { "{message}" }
+        }
+        form {
+            input {
+                r#type: "password",
+                placeholder: "Password",
+                value: password.read().clone(),
+                oninput: move |event| password.set(event.value()),
+            }
+            " "
+            button {
+                r#type: "submit",
+                onclick: move |event| {
+                    event.prevent_default();
+                    async move {
+                        let password = password.read().clone();
+                        if server::delete_account(password).await.is_err() {
+                            message.set("Failed to delete account".to_owned());
+                        } else {
+                            auth.set(None);
+                            navigator().push(Route::Home {});
+                        }
+                    }
+                },
+                "Delete account"
+            }
+            " "
+            Link { to: Route::Settings {}, "Cancel" }
         }
     }
 }
@@ -0,0 +1,75 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use crate::util::token::{generate_token, hash_token};
+
+/// What an API token may be used for, ordered from least to most privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(sqlx::Type))]
+#[cfg_attr(feature = "server", sqlx(type_name = "TEXT", rename_all = "lowercase"))]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Read-only access.
+    Read,
+    /// Read-write access.
+    Write,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 2] = [Scope::Read, Scope::Write];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = ();
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|s| s.as_str() == scope)
+            .ok_or(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    #[serde(default, skip_serializing)]
+    pub token_hash: String,
+    pub scope: Scope,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "server")]
+impl ApiToken {
+    /// Creates a token, returning it along with the secret to send as a
+    /// bearer token. Only the hash of the secret is kept.
+    pub fn new(name: String, scope: Scope, user_id: Uuid) -> (Self, String) {
+        let token = generate_token();
+
+        let api_token = ApiToken {
+            id: Uuid::new_v4(),
+            user_id,
+            name,
+            token_hash: hash_token(&token),
+            scope,
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+
+        (api_token, token)
+    }
+}
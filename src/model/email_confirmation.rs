@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use crate::util::token::{generate_token, hash_token};
+
+/// One-time token mailed to an address a user wants to use, which is only
+/// stored on the user once the link is opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailConfirmation {
+    pub token_hash: String,
+    pub user_id: Uuid,
+    pub email: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "server")]
+impl EmailConfirmation {
+    /// Creates a confirmation, returning it along with the token to put in
+    /// the link. Only the hash of the token is kept.
+    pub fn new(user_id: Uuid, email: String, expires_in: chrono::Duration) -> (Self, String) {
+        let token = generate_token();
+        let created_at = Utc::now();
+
+        let email_confirmation = EmailConfirmation {
+            token_hash: hash_token(&token),
+            user_id,
+            email,
+            created_at,
+            expires_at: created_at + expires_in,
+            used_at: None,
+        };
+
+        (email_confirmation, token)
+    }
+}
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Account at an external identity provider linked to a user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    pub issuer: String,
+    pub subject: String,
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "server")]
+impl Identity {
+    pub fn new(issuer: String, subject: String, user_id: Uuid) -> Self {
+        Identity {
+            issuer,
+            subject,
+            user_id,
+            created_at: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Role;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Invite {
+    pub id: Uuid,
+    pub list_id: Uuid,
+    pub role: Role,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "server")]
+impl Invite {
+    pub fn new(list_id: Uuid, role: Role, expires_in: chrono::Duration, user_id: Uuid) -> Self {
+        let created_at = Utc::now();
+
+        Invite {
+            id: Uuid::new_v4(),
+            list_id,
+            role,
+            created_at,
+            created_by: user_id,
+            expires_at: created_at + expires_in,
+            revoked_at: None,
+        }
+    }
+
+    /// Signed token to hand out in the invitation link.
+    pub fn token(&self) -> String {
+        crate::util::signature::sign(&self.id.to_string())
+    }
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A tag a user puts on tasks of any list they can access. Labels are
+/// private, so members of a shared list each see their own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Label {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    /// Hex color such as `#ff8800`.
+    pub color: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Label `label_id` is on task `task_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaskLabel {
+    pub task_id: Uuid,
+    pub label_id: Uuid,
+}
+
+#[cfg(feature = "server")]
+impl Label {
+    pub fn new(name: String, color: String, user_id: Uuid) -> Self {
+        Label {
+            id: Uuid::new_v4(),
+            user_id,
+            name,
+            color,
+            created_at: Utc::now(),
+        }
+    }
+}
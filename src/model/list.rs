@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::Role;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct List {
     pub id: Uuid,
@@ -9,6 +11,8 @@ pub struct List {
     pub created_at: DateTime<Utc>,
     pub created_by: Uuid,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// Role of the requesting user on this list.
+    pub role: Role,
 }
 
 #[cfg(feature = "server")]
@@ -20,6 +24,7 @@ impl List {
             created_at: Utc::now(),
             created_by: user_id,
             deleted_at: None,
+            role: Role::Owner,
         }
     }
 }
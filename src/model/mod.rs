@@ -1,9 +1,35 @@
+mod api_token;
+#[cfg(feature = "server")]
+mod email_confirmation;
+#[cfg(feature = "server")]
+mod identity;
+mod invite;
+mod label;
 mod list;
-mod task;
+mod member;
 #[cfg(feature = "server")]
+mod password_reset;
+mod saved_filter;
+mod search;
+mod session;
+mod task;
+mod totp;
 mod user;
 
+pub use api_token::*;
+#[cfg(feature = "server")]
+pub use email_confirmation::*;
+#[cfg(feature = "server")]
+pub use identity::*;
+pub use invite::*;
+pub use label::*;
 pub use list::*;
-pub use task::*;
+pub use member::*;
 #[cfg(feature = "server")]
+pub use password_reset::*;
+pub use saved_filter::*;
+pub use search::*;
+pub use session::*;
+pub use task::*;
+pub use totp::*;
 pub use user::*;
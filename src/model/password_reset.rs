@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use crate::util::token::{generate_token, hash_token};
+
+/// One-time token mailed to a user who forgot their password.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasswordReset {
+    pub token_hash: String,
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "server")]
+impl PasswordReset {
+    /// Creates a reset, returning it along with the token to put in the
+    /// link. Only the hash of the token is kept.
+    pub fn new(user_id: Uuid, expires_in: chrono::Duration) -> (Self, String) {
+        let token = generate_token();
+        let created_at = Utc::now();
+
+        let password_reset = PasswordReset {
+            token_hash: hash_token(&token),
+            user_id,
+            created_at,
+            expires_at: created_at + expires_in,
+            used_at: None,
+        };
+
+        (password_reset, token)
+    }
+}
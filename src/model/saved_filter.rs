@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A named filter of a user, written in the language of
+/// [`crate::util::filter`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub query: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "server")]
+impl SavedFilter {
+    pub fn new(name: String, query: String, user_id: Uuid) -> Self {
+        SavedFilter {
+            id: Uuid::new_v4(),
+            user_id,
+            name,
+            query,
+            created_at: Utc::now(),
+        }
+    }
+}
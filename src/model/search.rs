@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Marks the start of a match in [`SearchResult::snippet`].
+pub const HIGHLIGHT_START: char = '\u{2}';
+/// Marks the end of a match in [`SearchResult::snippet`].
+pub const HIGHLIGHT_END: char = '\u{3}';
+
+/// A task or a list matching a search, best matches first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub list_id: Uuid,
+    /// `None` when the list itself matched.
+    pub task_id: Option<Uuid>,
+    pub list_title: String,
+    /// Part of the matching title, with matches between
+    /// [`HIGHLIGHT_START`] and [`HIGHLIGHT_END`].
+    pub snippet: String,
+    /// Lower is better.
+    pub rank: f64,
+}
+
+impl SearchResult {
+    /// Parts of the snippet, each with whether it matched.
+    pub fn snippet_parts(&self) -> Vec<(String, bool)> {
+        let mut parts = Vec::new();
+        let mut part = String::new();
+        let mut highlighted = false;
+
+        for c in self.snippet.chars() {
+            if c == HIGHLIGHT_START || c == HIGHLIGHT_END {
+                if !part.is_empty() {
+                    parts.push((std::mem::take(&mut part), highlighted));
+                }
+                highlighted = c == HIGHLIGHT_START;
+            } else {
+                part.push(c);
+            }
+        }
+        if !part.is_empty() {
+            parts.push((part, highlighted));
+        }
+
+        parts
+    }
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A login of the user, as shown on the active sessions page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveSession {
+    /// Public id of the session, distinct from the secret one in the cookie.
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    /// Whether this is the session making the request.
+    #[serde(default)]
+    pub current: bool,
+}
+
+#[cfg(feature = "server")]
+impl ActiveSession {
+    pub fn new(user_agent: Option<String>, ip: Option<String>) -> Self {
+        let created_at = Utc::now();
+
+        ActiveSession {
+            id: Uuid::new_v4(),
+            created_at,
+            last_seen_at: created_at,
+            user_agent,
+            ip,
+            current: false,
+        }
+    }
+}
@@ -1,31 +1,340 @@
-use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg(feature = "server")]
+use crate::util::recurrence::Recurrence;
+#[cfg(feature = "server")]
+use crate::util::time_zone;
+
+/// How urgent a task is, ordered from least to most.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "server", derive(sqlx::Type))]
+#[serde(rename_all = "lowercase")]
+#[repr(i32)]
+pub enum Priority {
+    #[default]
+    None = 0,
+    Low = 1,
+    Medium = 2,
+    High = 3,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [
+        Priority::None,
+        Priority::Low,
+        Priority::Medium,
+        Priority::High,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::None => "none",
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = ();
+
+    fn from_str(priority: &str) -> Result<Self, Self::Err> {
+        Priority::ALL
+            .into_iter()
+            .find(|p| p.as_str() == priority)
+            .ok_or(())
+    }
+}
+
+/// Order tasks are shown in, the manual one being set by dragging them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskOrder {
+    #[default]
+    Manual,
+    DueDate,
+    Priority,
+    Created,
+}
+
+impl TaskOrder {
+    pub const ALL: [TaskOrder; 4] = [
+        TaskOrder::Manual,
+        TaskOrder::DueDate,
+        TaskOrder::Priority,
+        TaskOrder::Created,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskOrder::Manual => "manual",
+            TaskOrder::DueDate => "due date",
+            TaskOrder::Priority => "priority",
+            TaskOrder::Created => "creation",
+        }
+    }
+}
+
+impl FromStr for TaskOrder {
+    type Err = ();
+
+    fn from_str(order: &str) -> Result<Self, Self::Err> {
+        TaskOrder::ALL
+            .into_iter()
+            .find(|o| o.as_str() == order)
+            .ok_or(())
+    }
+}
+
+/// Built-in views gathering the tasks of every list of a user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SmartView {
+    /// Open tasks due today.
+    Today,
+    /// Open tasks due in the next days.
+    Upcoming,
+    /// Open tasks past their due time.
+    Overdue,
+    /// Tasks completed recently.
+    Completed,
+}
+
+impl SmartView {
+    /// Days after today covered by [`SmartView::Upcoming`].
+    #[cfg(feature = "server")]
+    pub const UPCOMING_DAYS: u64 = 7;
+    /// Days before today covered by [`SmartView::Completed`].
+    #[cfg(feature = "server")]
+    pub const COMPLETED_DAYS: u64 = 7;
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            SmartView::Today => "Today",
+            SmartView::Upcoming => "Upcoming",
+            SmartView::Overdue => "Overdue",
+            SmartView::Completed => "Completed",
+        }
+    }
+
+    /// Shown when the view has no task.
+    pub fn empty_message(&self) -> &'static str {
+        match self {
+            SmartView::Today => "Nothing is due today.",
+            SmartView::Upcoming => "Nothing is due in the next 7 days.",
+            SmartView::Overdue => "Nothing is overdue.",
+            SmartView::Completed => "Nothing was completed in the last 7 days.",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
 pub struct Task {
     pub id: Uuid,
     pub list_id: Uuid,
     pub title: String,
-    pub due_date: DateTime<Utc>,
+    /// Day the task is due, in the time zone of whoever set it.
+    pub due_date: Option<NaiveDate>,
+    /// Instant the task is due, when a time was set along with the date.
+    pub due_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub created_by: Uuid,
     pub completed_at: Option<DateTime<Utc>>,
     pub completed_by: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// RRULE the task repeats with, see [`crate::util::recurrence`].
+    pub recurrence: Option<String>,
+    /// Id of the first occurrence, shared by every occurrence of a recurring
+    /// task.
+    pub series_id: Option<Uuid>,
+    pub parent_id: Option<Uuid>,
+    /// Whether the task completes itself once all its subtasks are done.
+    pub auto_complete: bool,
+    pub priority: Priority,
+    /// Rank of the task among its siblings, lowest first.
+    pub position: f64,
+}
+
+/// A task along with its subtasks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskNode {
+    pub task: Task,
+    pub children: Vec<TaskNode>,
+}
+
+impl TaskNode {
+    /// Completed and total direct subtasks.
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self
+            .children
+            .iter()
+            .filter(|child| child.task.completed_at.is_some())
+            .count();
+
+        (done, self.children.len())
+    }
+
+    /// Every node of the trees with its depth, each parent right before its
+    /// subtasks.
+    pub fn flatten(nodes: &[TaskNode]) -> Vec<(usize, &TaskNode)> {
+        fn walk<'a>(nodes: &'a [TaskNode], depth: usize, flat: &mut Vec<(usize, &'a TaskNode)>) {
+            for node in nodes {
+                flat.push((depth, node));
+                walk(&node.children, depth + 1, flat);
+            }
+        }
+
+        let mut flat = Vec::new();
+        walk(nodes, 0, &mut flat);
+        flat
+    }
+
+    /// Sorts the trees in `order`, subtasks included. Ties keep their manual
+    /// order.
+    pub fn sort(nodes: &mut [TaskNode], order: TaskOrder) {
+        nodes.sort_by(|a, b| {
+            let (a, b) = (&a.task, &b.task);
+            let ordering = match order {
+                TaskOrder::Manual => Ordering::Equal,
+                // Tasks without a due date come last.
+                TaskOrder::DueDate => match (a.due_instant(), b.due_instant()) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+                TaskOrder::Priority => b.priority.cmp(&a.priority),
+                TaskOrder::Created => a.created_at.cmp(&b.created_at),
+            };
+
+            ordering.then(a.position.total_cmp(&b.position))
+        });
+
+        for node in nodes {
+            TaskNode::sort(&mut node.children, order);
+        }
+    }
+
+    /// Ids of the task and all of its subtasks, at any depth.
+    pub fn subtree_ids(&self) -> Vec<Uuid> {
+        TaskNode::flatten(std::slice::from_ref(self))
+            .into_iter()
+            .map(|(_, node)| node.task.id)
+            .collect()
+    }
+}
+
+impl Task {
+    /// Instant the task is due, taking a due date alone as the start of its
+    /// day in UTC.
+    fn due_instant(&self) -> Option<DateTime<Utc>> {
+        self.due_at.or_else(|| {
+            self.due_date
+                .map(|due_date| due_date.and_time(NaiveTime::MIN).and_utc())
+        })
+    }
+
+    /// Due date, and time if any, as seen from `tz`.
+    pub fn local_due(&self, tz: Tz) -> Option<(NaiveDate, Option<NaiveTime>)> {
+        match (self.due_at, self.due_date) {
+            (Some(due_at), _) => {
+                let due_at = due_at.with_timezone(&tz);
+                Some((due_at.date_naive(), Some(due_at.time())))
+            }
+            (None, Some(due_date)) => Some((due_date, None)),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether the task is still open past its due time, or past the end of
+    /// its due day in `tz` when it has no time.
+    pub fn is_overdue(&self, now: DateTime<Utc>, tz: Tz) -> bool {
+        if self.completed_at.is_some() {
+            return false;
+        }
+
+        match (self.due_at, self.due_date) {
+            (Some(due_at), _) => due_at < now,
+            (None, Some(due_date)) => due_date < now.with_timezone(&tz).date_naive(),
+            (None, None) => false,
+        }
+    }
 }
 
 #[cfg(feature = "server")]
 impl Task {
-    pub fn new(title: String, due_date: DateTime<Utc>, list_id: Uuid, user_id: Uuid) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        title: String,
+        due_date: Option<NaiveDate>,
+        due_at: Option<DateTime<Utc>>,
+        recurrence: Option<String>,
+        priority: Priority,
+        parent_id: Option<Uuid>,
+        list_id: Uuid,
+        user_id: Uuid,
+    ) -> Self {
+        let id = Uuid::new_v4();
+
         Task {
-            id: Uuid::new_v4(),
+            id,
             list_id,
             title,
             due_date,
+            due_at,
             created_at: Utc::now(),
             created_by: user_id,
             completed_at: None,
             completed_by: None,
+            deleted_at: None,
+            series_id: recurrence.as_ref().map(|_| id),
+            recurrence,
+            parent_id,
+            auto_complete: false,
+            priority,
+            // Set when the task is inserted, after its last sibling.
+            position: 0.0,
         }
     }
+
+    /// The occurrence following this one, at the same time of day in `tz`.
+    /// `None` when the task doesn't repeat or its rule is over after
+    /// `occurrences` occurrences.
+    pub fn next_occurrence(&self, occurrences: u32, tz: Tz, user_id: Uuid) -> Option<Task> {
+        let recurrence: Recurrence = self.recurrence.as_ref()?.parse().ok()?;
+
+        if recurrence.count.is_some_and(|count| occurrences >= count) {
+            return None;
+        }
+
+        let (due_date, due_time) = self.local_due(tz)?;
+        let due_date = recurrence.next_after(due_date)?;
+        // A time skipped by a daylight saving change moves to the hour after.
+        let due_at = due_time.map(|due_time| {
+            time_zone::to_utc(due_date, due_time, tz)
+                .or_else(|| time_zone::to_utc(due_date, due_time + chrono::Duration::hours(1), tz))
+                .unwrap_or_else(|| due_date.and_time(due_time).and_utc())
+        });
+
+        Some(Task {
+            id: Uuid::new_v4(),
+            due_date: Some(due_date),
+            due_at,
+            created_at: Utc::now(),
+            created_by: user_id,
+            completed_at: None,
+            completed_by: None,
+            deleted_at: None,
+            series_id: Some(self.series_id.unwrap_or(self.id)),
+            ..self.clone()
+        })
+    }
 }
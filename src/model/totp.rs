@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// Secret being enrolled, to be confirmed with a first code before it is
+/// enabled on the account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub uri: String,
+    /// SVG rendering of `uri`.
+    pub qr_code: String,
+}
@@ -1,26 +1,49 @@
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::util::password_hash::{hash_password, verify_password};
+#[cfg(feature = "server")]
+use crate::util::password_hash::hash_password;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
     #[serde(default, skip_serializing)]
     pub password_hash: String,
+    /// Set on hashes stored before each one had its own salt.
+    #[serde(default, skip_serializing)]
+    pub password_needs_rehash: bool,
+    #[serde(default, skip_serializing)]
+    pub totp_secret: Option<String>,
+    /// Where password reset links are sent, if the user gave one.
+    pub email: Option<String>,
+    /// IANA name of the time zone dates are shown in.
+    #[serde(default = "default_time_zone")]
+    pub time_zone: String,
+}
+
+fn default_time_zone() -> String {
+    Tz::UTC.name().to_owned()
+}
+
+impl User {
+    pub fn tz(&self) -> Tz {
+        self.time_zone.parse().unwrap_or(Tz::UTC)
+    }
 }
 
+#[cfg(feature = "server")]
 impl User {
     pub fn new(username: String, password: String) -> Self {
         Self {
             id: Uuid::new_v4(),
             username,
             password_hash: hash_password(&password),
+            password_needs_rehash: false,
+            totp_secret: None,
+            email: None,
+            time_zone: default_time_zone(),
         }
     }
-
-    pub fn verify_password(&self, password: &str) -> bool {
-        verify_password(password, &self.password_hash)
-    }
 }
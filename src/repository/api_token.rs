@@ -0,0 +1,87 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::model::ApiToken;
+
+use super::error::Result;
+
+impl ApiToken {
+    pub async fn create_api_token(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO api_tokens (id, user_id, name, token_hash, scope, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)",
+            self.id,
+            self.user_id,
+            self.name,
+            self.token_hash,
+            self.scope,
+            self.created_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_user_api_tokens(user_id: Uuid, pool: &SqlitePool) -> Result<Vec<ApiToken>> {
+        let tokens = sqlx::query_as!(
+            ApiToken,
+            r#"SELECT
+                id as "id: _",
+                user_id as "user_id: _",
+                name,
+                token_hash,
+                scope as "scope: _",
+                created_at as "created_at: _",
+                last_used_at as "last_used_at: _"
+            FROM api_tokens
+            WHERE user_id = ?
+            ORDER BY created_at"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    /// Finds the token with the given hash and records that it was used.
+    pub async fn use_api_token(token_hash: &str, pool: &SqlitePool) -> Result<Option<ApiToken>> {
+        let last_used_at = Utc::now();
+
+        let token = sqlx::query_as!(
+            ApiToken,
+            r#"UPDATE api_tokens SET last_used_at = ?
+            WHERE token_hash = ?
+            RETURNING
+                id as "id: _",
+                user_id as "user_id: _",
+                name,
+                token_hash,
+                scope as "scope: _",
+                created_at as "created_at: _",
+                last_used_at as "last_used_at: _""#,
+            last_used_at,
+            token_hash
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    pub async fn delete_api_token(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM api_tokens
+            WHERE id = ?
+            AND user_id = ?",
+            self.id,
+            self.user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
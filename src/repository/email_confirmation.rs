@@ -0,0 +1,87 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::model::EmailConfirmation;
+
+use super::error::Result;
+
+impl EmailConfirmation {
+    /// Stores the confirmation, replacing any earlier one the user hasn't
+    /// used yet so only the latest address can be confirmed.
+    pub async fn create_email_confirmation(&self, pool: &SqlitePool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM email_confirmations
+            WHERE user_id = ?
+            AND used_at IS NULL",
+            self.user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO email_confirmations (token_hash, user_id, email, created_at, expires_at)
+            VALUES (?, ?, ?, ?, ?)",
+            self.token_hash,
+            self.user_id,
+            self.email,
+            self.created_at,
+            self.expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Returns the confirmation with the given token hash if it is neither
+    /// used nor expired.
+    pub async fn get_valid_email_confirmation(
+        token_hash: &str,
+        pool: &SqlitePool,
+    ) -> Result<Option<EmailConfirmation>> {
+        let now = Utc::now();
+
+        let email_confirmation = sqlx::query_as!(
+            EmailConfirmation,
+            r#"SELECT
+                token_hash,
+                user_id as "user_id: _",
+                email,
+                created_at as "created_at: _",
+                expires_at as "expires_at: _",
+                used_at as "used_at: _"
+            FROM email_confirmations
+            WHERE token_hash = ?
+            AND used_at IS NULL
+            AND expires_at > ?"#,
+            token_hash,
+            now
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(email_confirmation)
+    }
+
+    /// Marks the confirmation as used. Returns `false` when it was used in
+    /// the meantime.
+    pub async fn use_email_confirmation(&self, pool: &SqlitePool) -> Result<bool> {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            "UPDATE email_confirmations SET used_at = ?
+            WHERE token_hash = ?
+            AND used_at IS NULL",
+            now,
+            self.token_hash
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
@@ -8,9 +8,39 @@ pub enum Error {
     #[error("User already exists")]
     UserAlreadyExists,
 
+    #[error("Email already used")]
+    EmailAlreadyUsed,
+
     #[error("User not found")]
     UserNotFound,
 
     #[error("Invalid credentials")]
     InvalidCredentials,
+
+    #[error("Too many attempts, try again in {0} seconds")]
+    TooManyAttempts(u64),
+
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    #[error("Forbidden")]
+    Forbidden,
+
+    #[error("Invalid or expired invite")]
+    InvalidInvite,
+
+    #[error("Invalid or expired password reset link")]
+    InvalidPasswordReset,
+
+    #[error("Invalid or expired email confirmation link")]
+    InvalidEmailConfirmation,
+
+    #[error("Two-factor authentication is already enabled")]
+    TotpAlreadyEnabled,
+
+    #[error("Label already exists")]
+    LabelAlreadyExists,
+
+    #[error("Filter already exists")]
+    FilterAlreadyExists,
 }
@@ -0,0 +1,46 @@
+use sqlx::SqlitePool;
+
+use crate::model::Identity;
+
+use super::error::Result;
+
+impl Identity {
+    pub async fn insert_identity(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO user_identities (issuer, subject, user_id, created_at)
+            VALUES (?, ?, ?, ?)",
+            self.issuer,
+            self.subject,
+            self.user_id,
+            self.created_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_identity(
+        issuer: &str,
+        subject: &str,
+        pool: &SqlitePool,
+    ) -> Result<Option<Identity>> {
+        let identity = sqlx::query_as!(
+            Identity,
+            r#"SELECT
+                issuer,
+                subject,
+                user_id as "user_id: _",
+                created_at as "created_at: _"
+            FROM user_identities
+            WHERE issuer = ?
+            AND subject = ?"#,
+            issuer,
+            subject
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(identity)
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::model::Invite;
+
+use super::error::Result;
+
+impl Invite {
+    pub async fn create_invite(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO invitations (id, list_id, role, created_at, created_by, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?)",
+            self.id,
+            self.list_id,
+            self.role,
+            self.created_at,
+            self.created_by,
+            self.expires_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_list_invites(list_id: Uuid, pool: &SqlitePool) -> Result<Vec<Invite>> {
+        let now = Utc::now();
+
+        let invites = sqlx::query_as!(
+            Invite,
+            r#"SELECT
+                id as "id: _",
+                list_id as "list_id: _",
+                role as "role: _",
+                created_at as "created_at: _",
+                created_by as "created_by: _",
+                expires_at as "expires_at: _",
+                revoked_at as "revoked_at: _"
+            FROM invitations
+            WHERE list_id = ?
+            AND revoked_at IS NULL
+            AND expires_at > ?"#,
+            list_id,
+            now
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(invites)
+    }
+
+    /// Returns the invite if it is neither revoked nor expired and its list
+    /// still exists.
+    pub async fn get_valid_invite(id: Uuid, pool: &SqlitePool) -> Result<Option<Invite>> {
+        let now = Utc::now();
+
+        let invite = sqlx::query_as!(
+            Invite,
+            r#"SELECT
+                invitations.id as "id: _",
+                list_id as "list_id: _",
+                role as "role: _",
+                invitations.created_at as "created_at: _",
+                invitations.created_by as "created_by: _",
+                expires_at as "expires_at: _",
+                revoked_at as "revoked_at: _"
+            FROM invitations
+            JOIN lists ON invitations.list_id = lists.id
+            WHERE invitations.id = ?
+            AND revoked_at IS NULL
+            AND expires_at > ?
+            AND lists.deleted_at IS NULL"#,
+            id,
+            now
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(invite)
+    }
+
+    pub async fn revoke_invite(&self, pool: &SqlitePool) -> Result<()> {
+        let revoked_at = Utc::now();
+
+        sqlx::query!(
+            "UPDATE invitations SET revoked_at = ?
+            WHERE id = ?
+            AND revoked_at IS NULL",
+            revoked_at,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::model::{Label, TaskLabel};
+
+use super::error::{Error, Result};
+
+impl Label {
+    pub async fn create_label(&self, pool: &SqlitePool) -> Result<()> {
+        let result = sqlx::query!(
+            "INSERT INTO labels (id, user_id, name, color, created_at)
+            VALUES (?, ?, ?, ?, ?)",
+            self.id,
+            self.user_id,
+            self.name,
+            self.color,
+            self.created_at
+        )
+        .execute(pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                Err(Error::LabelAlreadyExists)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub async fn get_user_labels(user_id: Uuid, pool: &SqlitePool) -> Result<Vec<Label>> {
+        let labels = sqlx::query_as!(
+            Label,
+            r#"SELECT
+                id as "id: _",
+                user_id as "user_id: _",
+                name,
+                color,
+                created_at as "created_at: _"
+            FROM labels
+            WHERE user_id = ?
+            ORDER BY name"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(labels)
+    }
+
+    /// Returns the label if it belongs to the user.
+    pub async fn get_label(id: Uuid, user_id: Uuid, pool: &SqlitePool) -> Result<Option<Label>> {
+        let label = sqlx::query_as!(
+            Label,
+            r#"SELECT
+                id as "id: _",
+                user_id as "user_id: _",
+                name,
+                color,
+                created_at as "created_at: _"
+            FROM labels
+            WHERE id = ?
+            AND user_id = ?"#,
+            id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(label)
+    }
+
+    /// Stores the name and color of the label.
+    pub async fn update_label(&self, pool: &SqlitePool) -> Result<()> {
+        let result = sqlx::query!(
+            "UPDATE labels SET name = ?, color = ?
+            WHERE id = ?",
+            self.name,
+            self.color,
+            self.id
+        )
+        .execute(pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                Err(Error::LabelAlreadyExists)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Deletes the label and takes it off every task.
+    pub async fn delete_label(&self, pool: &SqlitePool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!("DELETE FROM tasks_labels WHERE label_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM labels WHERE id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+impl TaskLabel {
+    pub async fn attach_label(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "INSERT OR IGNORE INTO tasks_labels (task_id, label_id)
+            VALUES (?, ?)",
+            self.task_id,
+            self.label_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn detach_label(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM tasks_labels
+            WHERE task_id = ?
+            AND label_id = ?",
+            self.task_id,
+            self.label_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Labels of the user on the tasks of the list.
+    pub async fn get_list_task_labels(
+        list_id: Uuid,
+        user_id: Uuid,
+        pool: &SqlitePool,
+    ) -> Result<Vec<TaskLabel>> {
+        let task_labels = sqlx::query_as!(
+            TaskLabel,
+            r#"SELECT
+                tasks_labels.task_id as "task_id: _",
+                tasks_labels.label_id as "label_id: _"
+            FROM tasks_labels
+            JOIN tasks ON tasks.id = tasks_labels.task_id
+            JOIN labels ON labels.id = tasks_labels.label_id
+            WHERE tasks.list_id = ?
+            AND labels.user_id = ?"#,
+            list_id,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(task_labels)
+    }
+}
@@ -1,10 +1,10 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use crate::model::List;
+use crate::model::{List, Role};
 
-use super::error::Result;
+use super::error::{Error, Result};
 
 impl List {
     pub async fn create_list(&self, pool: &SqlitePool) -> Result<()> {
@@ -26,14 +26,23 @@ impl List {
         let result = sqlx::query_as!(
             List,
             r#"SELECT
-                id as "id: _",
+                lists.id as "id: _",
                 title,
                 created_at as "created_at: _",
                 created_by as "created_by: _",
-                deleted_at as "deleted_at: _"
+                deleted_at as "deleted_at: _",
+                CASE
+                    WHEN created_by = ? THEN 'owner'
+                    ELSE lists_users.role
+                END as "role!: _"
             FROM lists
+            LEFT JOIN lists_users
+                ON lists_users.list_id = lists.id
+                AND lists_users.user_id = ?
             WHERE deleted_at IS NULL
-            AND created_by = ?"#,
+            AND (created_by = ? OR lists_users.user_id IS NOT NULL)"#,
+            user_id,
+            user_id,
             user_id
         )
         .fetch_all(pool)
@@ -42,6 +51,47 @@ impl List {
         Ok(result)
     }
 
+    /// Returns the role of the user on the list, or `None` when the user
+    /// neither owns nor is a member of it.
+    pub async fn get_role(list_id: Uuid, user_id: Uuid, pool: &SqlitePool) -> Result<Option<Role>> {
+        let role = sqlx::query_scalar!(
+            r#"SELECT
+                CASE
+                    WHEN created_by = ? THEN 'owner'
+                    ELSE lists_users.role
+                END as "role!: Role"
+            FROM lists
+            LEFT JOIN lists_users
+                ON lists_users.list_id = lists.id
+                AND lists_users.user_id = ?
+            WHERE lists.id = ?
+            AND deleted_at IS NULL
+            AND (created_by = ? OR lists_users.user_id IS NOT NULL)"#,
+            user_id,
+            user_id,
+            list_id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(role)
+    }
+
+    /// Ensures the user has at least the `required` role on the list,
+    /// returning [`Error::Forbidden`] otherwise.
+    pub async fn authorize(
+        list_id: Uuid,
+        user_id: Uuid,
+        required: Role,
+        pool: &SqlitePool,
+    ) -> Result<Role> {
+        match List::get_role(list_id, user_id, pool).await? {
+            Some(role) if role >= required => Ok(role),
+            _ => Err(Error::Forbidden),
+        }
+    }
+
     pub async fn delete_list(&self, pool: &SqlitePool) -> Result<()> {
         let deleted_at = Utc::now();
 
@@ -57,4 +107,264 @@ impl List {
 
         Ok(())
     }
+
+    pub async fn rename_list(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "UPDATE lists SET title = ?
+            WHERE id = ?
+            AND deleted_at IS NULL",
+            self.title,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deleted lists the user owns, as the creator or an owner member, most
+    /// recently deleted first.
+    pub async fn get_deleted_lists(user_id: Uuid, pool: &SqlitePool) -> Result<Vec<List>> {
+        let lists = sqlx::query_as!(
+            List,
+            r#"SELECT
+                lists.id as "id: _",
+                title,
+                created_at as "created_at: _",
+                created_by as "created_by: _",
+                deleted_at as "deleted_at: _",
+                'owner' as "role!: _"
+            FROM lists
+            LEFT JOIN lists_users
+                ON lists_users.list_id = lists.id
+                AND lists_users.user_id = ?
+            WHERE deleted_at IS NOT NULL
+            AND (created_by = ? OR lists_users.role = 'owner')
+            ORDER BY deleted_at DESC"#,
+            user_id,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(lists)
+    }
+
+    /// Returns the list if it is deleted and the user owns it, with the same
+    /// role check as [`List::authorize`] for deleting it.
+    pub async fn get_deleted_list(
+        id: Uuid,
+        user_id: Uuid,
+        pool: &SqlitePool,
+    ) -> Result<Option<List>> {
+        let list = sqlx::query_as!(
+            List,
+            r#"SELECT
+                lists.id as "id: _",
+                title,
+                created_at as "created_at: _",
+                created_by as "created_by: _",
+                deleted_at as "deleted_at: _",
+                'owner' as "role!: _"
+            FROM lists
+            LEFT JOIN lists_users
+                ON lists_users.list_id = lists.id
+                AND lists_users.user_id = ?
+            WHERE lists.id = ?
+            AND deleted_at IS NOT NULL
+            AND (created_by = ? OR lists_users.role = 'owner')"#,
+            user_id,
+            id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(list)
+    }
+
+    pub async fn restore_list(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "UPDATE lists SET deleted_at = NULL
+            WHERE id = ?",
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Permanently deletes the list along with its tasks, members and
+    /// invites.
+    pub async fn purge_list(&self, pool: &SqlitePool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM tasks_labels
+            WHERE task_id IN (SELECT id FROM tasks WHERE list_id = ?)",
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM tasks WHERE list_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM invitations WHERE list_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM lists_users WHERE list_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM lists WHERE id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Permanently deletes every list deleted before `deleted_before`, along
+    /// with their tasks, members and invites. Returns how many were purged.
+    pub async fn purge_deleted_lists(
+        deleted_before: DateTime<Utc>,
+        pool: &SqlitePool,
+    ) -> Result<u64> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM tasks_labels
+            WHERE task_id IN (
+                SELECT tasks.id FROM tasks
+                JOIN lists ON lists.id = tasks.list_id
+                WHERE lists.deleted_at < ?
+            )",
+            deleted_before
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM tasks
+            WHERE list_id IN (SELECT id FROM lists WHERE deleted_at < ?)",
+            deleted_before
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM invitations
+            WHERE list_id IN (SELECT id FROM lists WHERE deleted_at < ?)",
+            deleted_before
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM lists_users
+            WHERE list_id IN (SELECT id FROM lists WHERE deleted_at < ?)",
+            deleted_before
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!("DELETE FROM lists WHERE deleted_at < ?", deleted_before)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Member;
+    use crate::repository::{test_pool, test_user};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn authorize_forbids_non_members() {
+        let pool = test_pool().await;
+        let owner = test_user("owner", &pool).await;
+        let stranger = test_user("stranger", &pool).await;
+        let list = List::new("Groceries".to_owned(), owner.id);
+        list.create_list(&pool).await.unwrap();
+
+        // Reading, editing and deleting respectively.
+        for required in [Role::Viewer, Role::Editor, Role::Owner] {
+            let result = List::authorize(list.id, stranger.id, required, &pool).await;
+            assert!(matches!(result, Err(Error::Forbidden)), "{required:?}");
+        }
+        let role = List::authorize(list.id, owner.id, Role::Owner, &pool).await;
+        assert!(matches!(role, Ok(Role::Owner)));
+    }
+
+    #[tokio::test]
+    async fn authorize_checks_member_role() {
+        let pool = test_pool().await;
+        let owner = test_user("owner", &pool).await;
+        let viewer = test_user("viewer", &pool).await;
+        let list = List::new("Groceries".to_owned(), owner.id);
+        list.create_list(&pool).await.unwrap();
+        let member = Member {
+            list_id: list.id,
+            user_id: viewer.id,
+            username: viewer.username.clone(),
+            role: Role::Viewer,
+        };
+        member.insert_member(&pool).await.unwrap();
+
+        let role = List::authorize(list.id, viewer.id, Role::Viewer, &pool).await;
+        assert!(matches!(role, Ok(Role::Viewer)));
+        for required in [Role::Editor, Role::Owner] {
+            let result = List::authorize(list.id, viewer.id, required, &pool).await;
+            assert!(matches!(result, Err(Error::Forbidden)), "{required:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn co_owners_see_and_restore_deleted_lists() {
+        let pool = test_pool().await;
+        let creator = test_user("creator", &pool).await;
+        let co_owner = test_user("co-owner", &pool).await;
+        let editor = test_user("editor", &pool).await;
+        let list = List::new("Groceries".to_owned(), creator.id);
+        list.create_list(&pool).await.unwrap();
+        for (user, role) in [(&co_owner, Role::Owner), (&editor, Role::Editor)] {
+            let member = Member {
+                list_id: list.id,
+                user_id: user.id,
+                username: user.username.clone(),
+                role,
+            };
+            member.insert_member(&pool).await.unwrap();
+        }
+        list.delete_list(&pool).await.unwrap();
+
+        for user in [&creator, &co_owner] {
+            let deleted = List::get_deleted_lists(user.id, &pool).await.unwrap();
+            assert_eq!(deleted.len(), 1, "{}", user.username);
+        }
+        assert_eq!(List::get_deleted_lists(editor.id, &pool).await.unwrap(), []);
+        let deleted = List::get_deleted_list(list.id, editor.id, &pool)
+            .await
+            .unwrap();
+        assert_eq!(deleted, None);
+
+        let deleted = List::get_deleted_list(list.id, co_owner.id, &pool)
+            .await
+            .unwrap()
+            .unwrap();
+        deleted.restore_list(&pool).await.unwrap();
+        let role = List::authorize(list.id, co_owner.id, Role::Owner, &pool).await;
+        assert!(matches!(role, Ok(Role::Owner)));
+    }
 }
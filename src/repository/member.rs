@@ -0,0 +1,56 @@
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::model::Member;
+
+use super::error::Result;
+
+impl Member {
+    pub async fn insert_member(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO lists_users (list_id, user_id, role)
+            VALUES (?, ?, ?)
+            ON CONFLICT (list_id, user_id) DO UPDATE SET role = excluded.role",
+            self.list_id,
+            self.user_id,
+            self.role
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_list_members(list_id: Uuid, pool: &SqlitePool) -> Result<Vec<Member>> {
+        let members = sqlx::query_as!(
+            Member,
+            r#"SELECT
+                list_id as "list_id: _",
+                user_id as "user_id: _",
+                users.username,
+                role as "role: _"
+            FROM lists_users
+            JOIN users ON lists_users.user_id = users.id
+            WHERE list_id = ?"#,
+            list_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(members)
+    }
+
+    pub async fn delete_member(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM lists_users
+            WHERE list_id = ?
+            AND user_id = ?",
+            self.list_id,
+            self.user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,52 @@
+mod api_token;
+mod email_confirmation;
 pub mod error;
+mod identity;
+mod invite;
+mod label;
 mod list;
+mod member;
+mod password_reset;
+mod saved_filter;
+mod search;
+pub mod session;
 mod task;
+mod totp;
 mod user;
+
+/// Fresh in-memory database with every migration applied. A single
+/// connection is kept so all queries see the same database.
+#[cfg(test)]
+pub(crate) async fn test_pool() -> sqlx::SqlitePool {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::raw_sql(include_str!("../../migration/init.sql"))
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::migrate!("./migration").run(&pool).await.unwrap();
+    pool
+}
+
+/// Inserts a user without hashing a password, which is slow.
+#[cfg(test)]
+pub(crate) async fn test_user(username: &str, pool: &sqlx::SqlitePool) -> crate::model::User {
+    let user = crate::model::User {
+        id: uuid::Uuid::new_v4(),
+        username: username.to_owned(),
+        password_hash: String::new(),
+        password_needs_rehash: false,
+        totp_secret: None,
+        email: None,
+        time_zone: "UTC".to_owned(),
+    };
+    user.insert_user(pool).await.unwrap();
+    user
+}
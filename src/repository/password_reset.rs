@@ -0,0 +1,85 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::model::PasswordReset;
+
+use super::error::Result;
+
+impl PasswordReset {
+    /// Stores the reset, replacing any earlier one the user hasn't used yet
+    /// so only the latest link works.
+    pub async fn create_password_reset(&self, pool: &SqlitePool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM password_resets
+            WHERE user_id = ?
+            AND used_at IS NULL",
+            self.user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO password_resets (token_hash, user_id, created_at, expires_at)
+            VALUES (?, ?, ?, ?)",
+            self.token_hash,
+            self.user_id,
+            self.created_at,
+            self.expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Returns the reset with the given token hash if it is neither used nor
+    /// expired.
+    pub async fn get_valid_password_reset(
+        token_hash: &str,
+        pool: &SqlitePool,
+    ) -> Result<Option<PasswordReset>> {
+        let now = Utc::now();
+
+        let password_reset = sqlx::query_as!(
+            PasswordReset,
+            r#"SELECT
+                token_hash,
+                user_id as "user_id: _",
+                created_at as "created_at: _",
+                expires_at as "expires_at: _",
+                used_at as "used_at: _"
+            FROM password_resets
+            WHERE token_hash = ?
+            AND used_at IS NULL
+            AND expires_at > ?"#,
+            token_hash,
+            now
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(password_reset)
+    }
+
+    /// Marks the reset as used. Returns `false` when it was used in the
+    /// meantime, so a token can never reset a password twice.
+    pub async fn use_password_reset(&self, pool: &SqlitePool) -> Result<bool> {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            "UPDATE password_resets SET used_at = ?
+            WHERE token_hash = ?
+            AND used_at IS NULL",
+            now,
+            self.token_hash
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
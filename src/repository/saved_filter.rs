@@ -0,0 +1,108 @@
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::model::SavedFilter;
+
+use super::error::{Error, Result};
+
+impl SavedFilter {
+    pub async fn create_saved_filter(&self, pool: &SqlitePool) -> Result<()> {
+        let result = sqlx::query!(
+            "INSERT INTO saved_filters (id, user_id, name, query, created_at)
+            VALUES (?, ?, ?, ?, ?)",
+            self.id,
+            self.user_id,
+            self.name,
+            self.query,
+            self.created_at
+        )
+        .execute(pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                Err(Error::FilterAlreadyExists)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub async fn get_user_saved_filters(
+        user_id: Uuid,
+        pool: &SqlitePool,
+    ) -> Result<Vec<SavedFilter>> {
+        let saved_filters = sqlx::query_as!(
+            SavedFilter,
+            r#"SELECT
+                id as "id: _",
+                user_id as "user_id: _",
+                name,
+                query,
+                created_at as "created_at: _"
+            FROM saved_filters
+            WHERE user_id = ?
+            ORDER BY name"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(saved_filters)
+    }
+
+    /// Returns the filter if it belongs to the user.
+    pub async fn get_saved_filter(
+        id: Uuid,
+        user_id: Uuid,
+        pool: &SqlitePool,
+    ) -> Result<Option<SavedFilter>> {
+        let saved_filter = sqlx::query_as!(
+            SavedFilter,
+            r#"SELECT
+                id as "id: _",
+                user_id as "user_id: _",
+                name,
+                query,
+                created_at as "created_at: _"
+            FROM saved_filters
+            WHERE id = ?
+            AND user_id = ?"#,
+            id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(saved_filter)
+    }
+
+    /// Stores the name and query of the filter.
+    pub async fn update_saved_filter(&self, pool: &SqlitePool) -> Result<()> {
+        let result = sqlx::query!(
+            "UPDATE saved_filters SET name = ?, query = ?
+            WHERE id = ?",
+            self.name,
+            self.query,
+            self.id
+        )
+        .execute(pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                Err(Error::FilterAlreadyExists)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub async fn delete_saved_filter(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!("DELETE FROM saved_filters WHERE id = ?", self.id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::model::{SearchResult, HIGHLIGHT_END, HIGHLIGHT_START};
+
+use super::error::Result;
+
+/// Turns what the user typed into an FTS5 query matching every word, the
+/// last one as a prefix since it may not be fully typed yet. Words are
+/// quoted so the FTS5 syntax can't be used.
+fn fts_query(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+
+    if words.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}*", words.join(" ")))
+}
+
+impl SearchResult {
+    /// Tasks and lists the user can access whose title matches `text`.
+    /// Tasks have no notes yet; once they do, `tasks_search` needs a column
+    /// and triggers for them in a new migration.
+    pub async fn search(
+        text: &str,
+        user_id: Uuid,
+        limit: u32,
+        pool: &SqlitePool,
+    ) -> Result<Vec<SearchResult>> {
+        let Some(query) = fts_query(text) else {
+            return Ok(Vec::new());
+        };
+        let highlight_start = HIGHLIGHT_START.to_string();
+        let highlight_end = HIGHLIGHT_END.to_string();
+
+        let results = sqlx::query_as!(
+            SearchResult,
+            r#"SELECT
+                list_id as "list_id!: Uuid",
+                task_id as "task_id: Uuid",
+                list_title as "list_title!: String",
+                snippet as "snippet!: String",
+                rank as "rank!: f64"
+            FROM (
+                SELECT
+                    lists.id as list_id,
+                    tasks.id as task_id,
+                    lists.title as list_title,
+                    snippet(tasks_search, 1, ?, ?, '…', 16) as snippet,
+                    bm25(tasks_search) as rank
+                FROM tasks_search
+                JOIN tasks ON tasks.id = tasks_search.id
+                JOIN lists ON lists.id = tasks.list_id
+                LEFT JOIN lists_users
+                    ON lists_users.list_id = lists.id
+                    AND lists_users.user_id = ?
+                WHERE tasks_search MATCH ?
+                AND tasks.deleted_at IS NULL
+                AND lists.deleted_at IS NULL
+                AND (lists.created_by = ? OR lists_users.user_id IS NOT NULL)
+
+                UNION ALL
+
+                SELECT
+                    lists.id as list_id,
+                    NULL as task_id,
+                    lists.title as list_title,
+                    snippet(lists_search, 1, ?, ?, '…', 16) as snippet,
+                    bm25(lists_search) as rank
+                FROM lists_search
+                JOIN lists ON lists.id = lists_search.id
+                LEFT JOIN lists_users
+                    ON lists_users.list_id = lists.id
+                    AND lists_users.user_id = ?
+                WHERE lists_search MATCH ?
+                AND lists.deleted_at IS NULL
+                AND (lists.created_by = ? OR lists_users.user_id IS NOT NULL)
+            )
+            ORDER BY rank
+            LIMIT ?"#,
+            highlight_start,
+            highlight_end,
+            user_id,
+            query,
+            user_id,
+            highlight_start,
+            highlight_end,
+            user_id,
+            query,
+            user_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,286 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use time::OffsetDateTime;
+use tower_sessions::session::{Id, Record};
+use tower_sessions::session_store::{self, ExpiredDeletion, SessionStore};
+use uuid::Uuid;
+
+use crate::model::ActiveSession;
+
+use super::error::Result;
+
+/// Session store persisting records to the `sessions` table.
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Deletes every session of the user, except the `current` one if given.
+    pub async fn delete_user_sessions(
+        &self,
+        user_id: Uuid,
+        current: Option<Id>,
+    ) -> session_store::Result<()> {
+        let current = current.map(|id| id.to_string());
+
+        sqlx::query!(
+            "DELETE FROM sessions
+            WHERE user_id = ?
+            AND id IS NOT ?",
+            user_id,
+            current
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(backend)?;
+
+        Ok(())
+    }
+}
+
+fn backend(error: sqlx::Error) -> session_store::Error {
+    session_store::Error::Backend(error.to_string())
+}
+
+fn to_datetime(date: OffsetDateTime) -> DateTime<Utc> {
+    DateTime::from_timestamp(date.unix_timestamp(), date.nanosecond()).unwrap_or_default()
+}
+
+/// Values of the `sessions` columns derived from a record.
+struct Columns {
+    id: String,
+    user_id: Option<Uuid>,
+    data: String,
+    expires_at: DateTime<Utc>,
+    public_id: Option<Uuid>,
+    created_at: Option<DateTime<Utc>>,
+    user_agent: Option<String>,
+    ip: Option<String>,
+}
+
+impl Columns {
+    fn new(record: &Record) -> session_store::Result<Self> {
+        let data = serde_json::to_string(&record.data)
+            .map_err(|error| session_store::Error::Encode(error.to_string()))?;
+
+        // Id of the user stored in the session, if someone is logged in.
+        let user_id = record
+            .data
+            .get("user")
+            .and_then(|user| user.get("id")?.as_str()?.parse().ok());
+
+        let info: Option<ActiveSession> = record
+            .data
+            .get("info")
+            .and_then(|info| serde_json::from_value(info.clone()).ok());
+
+        Ok(Columns {
+            id: record.id.to_string(),
+            user_id,
+            data,
+            expires_at: to_datetime(record.expiry_date),
+            public_id: info.as_ref().map(|info| info.id),
+            created_at: info.as_ref().map(|info| info.created_at),
+            user_agent: info.as_ref().and_then(|info| info.user_agent.clone()),
+            ip: info.and_then(|info| info.ip),
+        })
+    }
+}
+
+#[async_trait]
+impl SessionStore for SqliteStore {
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        loop {
+            let columns = Columns::new(record)?;
+
+            let result = sqlx::query!(
+                "INSERT INTO sessions (
+                    id, user_id, data, expires_at,
+                    public_id, created_at, last_seen_at, user_agent, ip
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT (id) DO NOTHING",
+                columns.id,
+                columns.user_id,
+                columns.data,
+                columns.expires_at,
+                columns.public_id,
+                columns.created_at,
+                columns.created_at,
+                columns.user_agent,
+                columns.ip
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+
+            if result.rows_affected() > 0 {
+                return Ok(());
+            }
+
+            // Session ID collision mitigation.
+            record.id = Id::default();
+        }
+    }
+
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        let columns = Columns::new(record)?;
+
+        sqlx::query!(
+            "INSERT INTO sessions (
+                id, user_id, data, expires_at,
+                public_id, created_at, last_seen_at, user_agent, ip
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (id) DO UPDATE SET
+                user_id = excluded.user_id,
+                data = excluded.data,
+                expires_at = excluded.expires_at,
+                public_id = excluded.public_id,
+                created_at = excluded.created_at,
+                last_seen_at = COALESCE(last_seen_at, excluded.last_seen_at),
+                user_agent = excluded.user_agent,
+                ip = excluded.ip",
+            columns.id,
+            columns.user_id,
+            columns.data,
+            columns.expires_at,
+            columns.public_id,
+            columns.created_at,
+            columns.created_at,
+            columns.user_agent,
+            columns.ip
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(backend)?;
+
+        Ok(())
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        let id = session_id.to_string();
+        let now = Utc::now();
+
+        let row = sqlx::query!(
+            r#"SELECT
+                data,
+                expires_at as "expires_at: DateTime<Utc>"
+            FROM sessions
+            WHERE id = ?
+            AND expires_at > ?"#,
+            id,
+            now
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(backend)?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let data = serde_json::from_str(&row.data)
+            .map_err(|error| session_store::Error::Decode(error.to_string()))?;
+        let expiry_date = OffsetDateTime::from_unix_timestamp(row.expires_at.timestamp())
+            .map_err(|error| session_store::Error::Decode(error.to_string()))?;
+
+        Ok(Some(Record {
+            id: *session_id,
+            data,
+            expiry_date,
+        }))
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        let id = session_id.to_string();
+
+        sqlx::query!("DELETE FROM sessions WHERE id = ?", id)
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ExpiredDeletion for SqliteStore {
+    async fn delete_expired(&self) -> session_store::Result<()> {
+        let now = Utc::now();
+
+        sqlx::query!("DELETE FROM sessions WHERE expires_at <= ?", now)
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+
+        Ok(())
+    }
+}
+
+impl ActiveSession {
+    pub async fn get_user_sessions(user_id: Uuid, pool: &SqlitePool) -> Result<Vec<ActiveSession>> {
+        let now = Utc::now();
+
+        let sessions = sqlx::query_as!(
+            ActiveSession,
+            r#"SELECT
+                public_id as "id!: _",
+                created_at as "created_at!: _",
+                last_seen_at as "last_seen_at!: _",
+                user_agent,
+                ip,
+                FALSE as "current!: bool"
+            FROM sessions
+            WHERE user_id = ?
+            AND public_id IS NOT NULL
+            AND expires_at > ?
+            ORDER BY last_seen_at DESC"#,
+            user_id,
+            now
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    /// Records activity on the session, at most once a minute.
+    pub async fn touch_session(id: Uuid, pool: &SqlitePool) -> Result<()> {
+        let now = Utc::now();
+        let threshold = now - chrono::Duration::minutes(1);
+
+        sqlx::query!(
+            "UPDATE sessions SET last_seen_at = ?
+            WHERE public_id = ?
+            AND last_seen_at < ?",
+            now,
+            id,
+            threshold
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_session(id: Uuid, user_id: Uuid, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM sessions
+            WHERE public_id = ?
+            AND user_id = ?",
+            id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
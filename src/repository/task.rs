@@ -1,24 +1,46 @@
-use chrono::Utc;
-use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{QueryBuilder, Sqlite, SqliteExecutor, SqlitePool};
 use uuid::Uuid;
 
-use crate::model::Task;
+use crate::model::{Task, TaskNode};
+use crate::util::filter::{Condition, Day, Filter};
 
 use super::error::Result;
 
 impl Task {
     pub async fn create_task(&self, pool: &SqlitePool) -> Result<()> {
+        self.insert_task(pool).await
+    }
+
+    async fn insert_task(&self, executor: impl SqliteExecutor<'_>) -> Result<()> {
         sqlx::query!(
-            r#"INSERT INTO tasks (id, list_id, title, due_date, created_at, created_by)
-            VALUES (?, ?, ?, ?, ?, ?)"#,
+            r#"INSERT INTO tasks (
+                id, list_id, title, due_date, due_at, created_at, created_by, recurrence,
+                series_id, parent_id, auto_complete, priority, position
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, (
+                SELECT COALESCE(MAX(position), 0) + 1 FROM tasks
+                WHERE list_id = ?
+                AND parent_id IS ?
+            ))"#,
             self.id,
             self.list_id,
             self.title,
             self.due_date,
+            self.due_at,
             self.created_at,
-            self.created_by
+            self.created_by,
+            self.recurrence,
+            self.series_id,
+            self.parent_id,
+            self.auto_complete,
+            self.priority,
+            self.list_id,
+            self.parent_id
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
 
         Ok(())
@@ -32,13 +54,23 @@ impl Task {
                 list_id as "list_id: _",
                 title,
                 due_date as "due_date: _",
+                due_at as "due_at: _",
                 created_at as "created_at: _",
                 created_by as "created_by: _",
                 completed_at as "completed_at: _",
-                users.username as "completed_by: _"
+                users.username as "completed_by: _",
+                deleted_at as "deleted_at: _",
+                recurrence,
+                series_id as "series_id: _",
+                parent_id as "parent_id: _",
+                auto_complete,
+                priority as "priority: _",
+                position
             FROM tasks
             LEFT JOIN users ON tasks.completed_by = users.id
-            WHERE list_id = ?"#,
+            WHERE list_id = ?
+            AND deleted_at IS NULL
+            ORDER BY position, created_at"#,
             list_id
         )
         .fetch_all(pool)
@@ -47,21 +79,84 @@ impl Task {
         Ok(tasks)
     }
 
-    pub async fn complete_task(&self, user_id: Uuid, pool: &SqlitePool) -> Result<()> {
+    pub async fn get_task(id: Uuid, pool: &SqlitePool) -> Result<Option<Task>> {
+        let task = sqlx::query_as!(
+            Task,
+            r#"SELECT
+                tasks.id as "id: _",
+                list_id as "list_id: _",
+                title,
+                due_date as "due_date: _",
+                due_at as "due_at: _",
+                created_at as "created_at: _",
+                created_by as "created_by: _",
+                completed_at as "completed_at: _",
+                users.username as "completed_by: _",
+                deleted_at as "deleted_at: _",
+                recurrence,
+                series_id as "series_id: _",
+                parent_id as "parent_id: _",
+                auto_complete,
+                priority as "priority: _",
+                position
+            FROM tasks
+            LEFT JOIN users ON tasks.completed_by = users.id
+            WHERE tasks.id = ?
+            AND deleted_at IS NULL"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(task)
+    }
+
+    /// Completes the task and creates the `next` occurrence of a recurring
+    /// one, unless the task was already completed or another occurrence
+    /// already follows it.
+    pub async fn complete_task(
+        &self,
+        user_id: Uuid,
+        next: Option<&Task>,
+        pool: &SqlitePool,
+    ) -> Result<()> {
         let completed_at = Utc::now();
 
-        sqlx::query!(
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query!(
             r#"UPDATE tasks
             SET completed_at = ?,
                 completed_by = ?
-            WHERE id = ?"#,
+            WHERE id = ?
+            AND completed_at IS NULL"#,
             completed_at,
             user_id,
             self.id
         )
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
+        if let Some(next) = next.filter(|_| result.rows_affected() == 1) {
+            let followed = sqlx::query_scalar!(
+                r#"SELECT EXISTS (
+                    SELECT 1 FROM tasks
+                    WHERE series_id = ?
+                    AND created_at > ?
+                ) as "followed: bool""#,
+                next.series_id,
+                self.created_at
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if !followed {
+                next.insert_task(&mut *tx).await?;
+            }
+        }
+
+        tx.commit().await?;
+
         Ok(())
     }
 
@@ -78,4 +173,648 @@ impl Task {
 
         Ok(())
     }
+
+    /// Stores the title, due date, recurrence and priority of the task.
+    pub async fn update_task(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE tasks
+            SET title = ?,
+                due_date = ?,
+                due_at = ?,
+                recurrence = ?,
+                series_id = ?,
+                priority = ?
+            WHERE id = ?
+            AND deleted_at IS NULL"#,
+            self.title,
+            self.due_date,
+            self.due_at,
+            self.recurrence,
+            self.series_id,
+            self.priority,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the task along with its subtasks.
+    pub async fn delete_task(&self, pool: &SqlitePool) -> Result<()> {
+        let deleted_at = Utc::now();
+
+        sqlx::query!(
+            r#"WITH RECURSIVE subtree(id) AS (
+                SELECT ?
+                UNION
+                SELECT tasks.id FROM tasks
+                JOIN subtree ON tasks.parent_id = subtree.id
+            )
+            UPDATE tasks SET deleted_at = ?
+            WHERE id IN (SELECT id FROM subtree)
+            AND deleted_at IS NULL"#,
+            self.id,
+            deleted_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Number of occurrences created so far in the series, deleted ones
+    /// included.
+    pub async fn count_series_tasks(series_id: Uuid, pool: &SqlitePool) -> Result<u32> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count: u32" FROM tasks WHERE series_id = ?"#,
+            series_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Occurrences of a recurring task, oldest first.
+    pub async fn get_series_tasks(series_id: Uuid, pool: &SqlitePool) -> Result<Vec<Task>> {
+        let tasks = sqlx::query_as!(
+            Task,
+            r#"SELECT
+                tasks.id as "id: _",
+                list_id as "list_id: _",
+                title,
+                due_date as "due_date: _",
+                due_at as "due_at: _",
+                created_at as "created_at: _",
+                created_by as "created_by: _",
+                completed_at as "completed_at: _",
+                users.username as "completed_by: _",
+                deleted_at as "deleted_at: _",
+                recurrence,
+                series_id as "series_id: _",
+                parent_id as "parent_id: _",
+                auto_complete,
+                priority as "priority: _",
+                position
+            FROM tasks
+            LEFT JOIN users ON tasks.completed_by = users.id
+            WHERE series_id = ?
+            AND deleted_at IS NULL
+            ORDER BY due_date, created_at"#,
+            series_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Tasks carrying the label, from every list the user owns or is a member
+    /// of.
+    pub async fn get_label_tasks(
+        label_id: Uuid,
+        user_id: Uuid,
+        pool: &SqlitePool,
+    ) -> Result<Vec<Task>> {
+        let tasks = sqlx::query_as!(
+            Task,
+            r#"SELECT
+                tasks.id as "id: _",
+                tasks.list_id as "list_id: _",
+                tasks.title,
+                due_date as "due_date: _",
+                due_at as "due_at: _",
+                tasks.created_at as "created_at: _",
+                tasks.created_by as "created_by: _",
+                completed_at as "completed_at: _",
+                users.username as "completed_by: _",
+                tasks.deleted_at as "deleted_at: _",
+                recurrence,
+                series_id as "series_id: _",
+                parent_id as "parent_id: _",
+                auto_complete,
+                priority as "priority: _",
+                position
+            FROM tasks
+            JOIN tasks_labels ON tasks_labels.task_id = tasks.id
+            JOIN lists ON lists.id = tasks.list_id
+            LEFT JOIN lists_users
+                ON lists_users.list_id = lists.id
+                AND lists_users.user_id = ?
+            LEFT JOIN users ON tasks.completed_by = users.id
+            WHERE tasks_labels.label_id = ?
+            AND tasks.deleted_at IS NULL
+            AND lists.deleted_at IS NULL
+            AND (lists.created_by = ? OR lists_users.user_id IS NOT NULL)
+            ORDER BY lists.title, tasks.position, tasks.created_at"#,
+            user_id,
+            label_id,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Open tasks of every list of the user due from `start` included to `end`
+    /// excluded, given as days for tasks without a due time and as instants
+    /// for the others.
+    pub async fn get_user_tasks_due_between(
+        user_id: Uuid,
+        start_date: NaiveDate,
+        start_at: DateTime<Utc>,
+        end_date: NaiveDate,
+        end_at: DateTime<Utc>,
+        pool: &SqlitePool,
+    ) -> Result<Vec<Task>> {
+        let tasks = sqlx::query_as!(
+            Task,
+            r#"SELECT
+                tasks.id as "id: _",
+                tasks.list_id as "list_id: _",
+                tasks.title,
+                due_date as "due_date: _",
+                due_at as "due_at: _",
+                tasks.created_at as "created_at: _",
+                tasks.created_by as "created_by: _",
+                completed_at as "completed_at: _",
+                users.username as "completed_by: _",
+                tasks.deleted_at as "deleted_at: _",
+                recurrence,
+                series_id as "series_id: _",
+                parent_id as "parent_id: _",
+                auto_complete,
+                priority as "priority: _",
+                position
+            FROM tasks
+            JOIN lists ON lists.id = tasks.list_id
+            LEFT JOIN lists_users
+                ON lists_users.list_id = lists.id
+                AND lists_users.user_id = ?
+            LEFT JOIN users ON tasks.completed_by = users.id
+            WHERE completed_at IS NULL
+            AND (
+                (due_at IS NOT NULL AND due_at >= ? AND due_at < ?)
+                OR (due_at IS NULL AND due_date >= ? AND due_date < ?)
+            )
+            AND tasks.deleted_at IS NULL
+            AND lists.deleted_at IS NULL
+            AND (lists.created_by = ? OR lists_users.user_id IS NOT NULL)
+            ORDER BY lists.title, COALESCE(due_at, due_date), tasks.position"#,
+            user_id,
+            start_at,
+            end_at,
+            start_date,
+            end_date,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Open tasks of every list of the user due before `now`, or before
+    /// `today` for tasks without a due time.
+    pub async fn get_user_overdue_tasks(
+        user_id: Uuid,
+        today: NaiveDate,
+        now: DateTime<Utc>,
+        pool: &SqlitePool,
+    ) -> Result<Vec<Task>> {
+        let tasks = sqlx::query_as!(
+            Task,
+            r#"SELECT
+                tasks.id as "id: _",
+                tasks.list_id as "list_id: _",
+                tasks.title,
+                due_date as "due_date: _",
+                due_at as "due_at: _",
+                tasks.created_at as "created_at: _",
+                tasks.created_by as "created_by: _",
+                completed_at as "completed_at: _",
+                users.username as "completed_by: _",
+                tasks.deleted_at as "deleted_at: _",
+                recurrence,
+                series_id as "series_id: _",
+                parent_id as "parent_id: _",
+                auto_complete,
+                priority as "priority: _",
+                position
+            FROM tasks
+            JOIN lists ON lists.id = tasks.list_id
+            LEFT JOIN lists_users
+                ON lists_users.list_id = lists.id
+                AND lists_users.user_id = ?
+            LEFT JOIN users ON tasks.completed_by = users.id
+            WHERE completed_at IS NULL
+            AND (
+                (due_at IS NOT NULL AND due_at < ?)
+                OR (due_at IS NULL AND due_date < ?)
+            )
+            AND tasks.deleted_at IS NULL
+            AND lists.deleted_at IS NULL
+            AND (lists.created_by = ? OR lists_users.user_id IS NOT NULL)
+            ORDER BY lists.title, COALESCE(due_at, due_date), tasks.position"#,
+            user_id,
+            now,
+            today,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Tasks of every list of the user completed since `since`, latest
+    /// first.
+    pub async fn get_user_completed_tasks(
+        user_id: Uuid,
+        since: DateTime<Utc>,
+        pool: &SqlitePool,
+    ) -> Result<Vec<Task>> {
+        let tasks = sqlx::query_as!(
+            Task,
+            r#"SELECT
+                tasks.id as "id: _",
+                tasks.list_id as "list_id: _",
+                tasks.title,
+                due_date as "due_date: _",
+                due_at as "due_at: _",
+                tasks.created_at as "created_at: _",
+                tasks.created_by as "created_by: _",
+                completed_at as "completed_at: _",
+                users.username as "completed_by: _",
+                tasks.deleted_at as "deleted_at: _",
+                recurrence,
+                series_id as "series_id: _",
+                parent_id as "parent_id: _",
+                auto_complete,
+                priority as "priority: _",
+                position
+            FROM tasks
+            JOIN lists ON lists.id = tasks.list_id
+            LEFT JOIN lists_users
+                ON lists_users.list_id = lists.id
+                AND lists_users.user_id = ?
+            LEFT JOIN users ON tasks.completed_by = users.id
+            WHERE completed_at >= ?
+            AND tasks.deleted_at IS NULL
+            AND lists.deleted_at IS NULL
+            AND (lists.created_by = ? OR lists_users.user_id IS NOT NULL)
+            ORDER BY lists.title, completed_at DESC"#,
+            user_id,
+            since,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Tasks of every list of the user matching the filter, with relative
+    /// days counted from `today` and overdue tasks due before `now`.
+    pub async fn get_filtered_tasks(
+        filter: &Filter,
+        user_id: Uuid,
+        today: NaiveDate,
+        now: DateTime<Utc>,
+        pool: &SqlitePool,
+    ) -> Result<Vec<Task>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT
+                tasks.id,
+                tasks.list_id,
+                tasks.title,
+                tasks.due_date,
+                tasks.due_at,
+                tasks.created_at,
+                tasks.created_by,
+                tasks.completed_at,
+                users.username AS completed_by,
+                tasks.deleted_at,
+                tasks.recurrence,
+                tasks.series_id,
+                tasks.parent_id,
+                tasks.auto_complete,
+                tasks.priority,
+                tasks.position
+            FROM tasks
+            JOIN lists ON lists.id = tasks.list_id
+            LEFT JOIN lists_users
+                ON lists_users.list_id = lists.id
+                AND lists_users.user_id = ",
+        );
+        query.push_bind(user_id);
+        query.push(
+            "
+            LEFT JOIN users ON tasks.completed_by = users.id
+            WHERE tasks.deleted_at IS NULL
+            AND lists.deleted_at IS NULL
+            AND (lists.created_by = ",
+        );
+        query.push_bind(user_id);
+        query.push(" OR lists_users.user_id IS NOT NULL)");
+
+        for term in &filter.terms {
+            // A condition on a missing value is NULL, which a negation keeps
+            // out as well.
+            query.push(if term.negated {
+                " AND NOT COALESCE(("
+            } else {
+                " AND COALESCE(("
+            });
+            push_condition(&mut query, &term.condition, user_id, today, now);
+            query.push("), FALSE)");
+        }
+
+        query.push(" ORDER BY lists.title, tasks.position, tasks.created_at");
+
+        let tasks = query.build_query_as::<Task>().fetch_all(pool).await?;
+
+        Ok(tasks)
+    }
+
+    /// Tasks of the list arranged in trees. Subtasks of deleted tasks are
+    /// left out along with them.
+    pub async fn get_task_tree(list_id: Uuid, pool: &SqlitePool) -> Result<Vec<TaskNode>> {
+        let tasks = sqlx::query_as!(
+            Task,
+            r#"WITH RECURSIVE tree(id) AS (
+                SELECT id FROM tasks
+                WHERE list_id = ?
+                AND parent_id IS NULL
+                AND deleted_at IS NULL
+                UNION
+                SELECT tasks.id FROM tasks
+                JOIN tree ON tasks.parent_id = tree.id
+                WHERE tasks.deleted_at IS NULL
+            )
+            SELECT
+                tasks.id as "id: _",
+                list_id as "list_id: _",
+                title,
+                due_date as "due_date: _",
+                due_at as "due_at: _",
+                created_at as "created_at: _",
+                created_by as "created_by: _",
+                completed_at as "completed_at: _",
+                users.username as "completed_by: _",
+                deleted_at as "deleted_at: _",
+                recurrence,
+                series_id as "series_id: _",
+                parent_id as "parent_id: _",
+                auto_complete,
+                priority as "priority: _",
+                position
+            FROM tasks
+            LEFT JOIN users ON tasks.completed_by = users.id
+            WHERE tasks.id IN (SELECT id FROM tree)
+            ORDER BY position, created_at"#,
+            list_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut children: HashMap<Option<Uuid>, Vec<Task>> = HashMap::new();
+        for task in tasks {
+            children.entry(task.parent_id).or_default().push(task);
+        }
+
+        fn build(
+            parent_id: Option<Uuid>,
+            children: &mut HashMap<Option<Uuid>, Vec<Task>>,
+        ) -> Vec<TaskNode> {
+            children
+                .remove(&parent_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|task| TaskNode {
+                    children: build(Some(task.id), children),
+                    task,
+                })
+                .collect()
+        }
+
+        Ok(build(None, &mut children))
+    }
+
+    /// Ids of the task and of every task above it.
+    pub async fn get_ancestor_ids(id: Uuid, pool: &SqlitePool) -> Result<Vec<Uuid>> {
+        let ids = sqlx::query_scalar!(
+            r#"WITH RECURSIVE ancestors(id, parent_id) AS (
+                SELECT id, parent_id FROM tasks WHERE id = ?
+                UNION
+                SELECT tasks.id, tasks.parent_id FROM tasks
+                JOIN ancestors ON tasks.id = ancestors.parent_id
+            )
+            SELECT id as "id!: Uuid" FROM ancestors"#,
+            id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Number of subtasks of the task that are still open.
+    pub async fn count_open_subtasks(id: Uuid, pool: &SqlitePool) -> Result<u32> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count: u32" FROM tasks
+            WHERE parent_id = ?
+            AND completed_at IS NULL
+            AND deleted_at IS NULL"#,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Moves the task under `parent_id`, which must not be the task itself or
+    /// one of its subtasks, after its last subtask.
+    pub async fn move_task(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE tasks
+            SET parent_id = ?,
+                position = (
+                    SELECT COALESCE(MAX(position), 0) + 1 FROM tasks
+                    WHERE list_id = ?
+                    AND parent_id IS ?
+                )
+            WHERE id = ?
+            AND deleted_at IS NULL"#,
+            self.parent_id,
+            self.list_id,
+            self.parent_id,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_auto_complete(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE tasks SET auto_complete = ?
+            WHERE id = ?
+            AND deleted_at IS NULL"#,
+            self.auto_complete,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Moves the task to the place of `target`, one of its siblings: right
+    /// after it when the task was above, right before it otherwise.
+    ///
+    /// The task gets a rank between its new neighbours, and its siblings are
+    /// renumbered only once no rank is left between them.
+    pub async fn reorder_task(&self, target: &Task, pool: &SqlitePool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let siblings = sqlx::query!(
+            r#"SELECT id as "id: Uuid", position FROM tasks
+            WHERE list_id = ?
+            AND parent_id IS ?
+            AND deleted_at IS NULL
+            ORDER BY position, created_at"#,
+            self.list_id,
+            self.parent_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut ids: Vec<Uuid> = siblings.iter().map(|sibling| sibling.id).collect();
+        let mut positions: Vec<f64> = siblings.iter().map(|sibling| sibling.position).collect();
+
+        let (Some(from), Some(to)) = (
+            ids.iter().position(|&id| id == self.id),
+            ids.iter().position(|&id| id == target.id),
+        ) else {
+            return Ok(());
+        };
+
+        // Once the task is taken out, `to` is right after the target when the
+        // task was above it, and still on the target otherwise.
+        ids.remove(from);
+        positions.remove(from);
+
+        let before = to.checked_sub(1).map(|i| positions[i]);
+        let after = positions.get(to).copied();
+
+        match rank_between(before, after) {
+            Some(position) => {
+                sqlx::query!(
+                    "UPDATE tasks SET position = ? WHERE id = ?",
+                    position,
+                    self.id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                ids.insert(to, self.id);
+                for (index, id) in ids.into_iter().enumerate() {
+                    let position = (index + 1) as f64;
+                    sqlx::query!("UPDATE tasks SET position = ? WHERE id = ?", position, id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+/// A rank strictly between `before` and `after`, or `None` when they are too
+/// close for one to fit.
+fn rank_between(before: Option<f64>, after: Option<f64>) -> Option<f64> {
+    match (before, after) {
+        (None, None) => Some(1.0),
+        (Some(before), None) => Some(before + 1.0),
+        (None, Some(after)) => Some(after - 1.0),
+        (Some(before), Some(after)) => {
+            let position = before + (after - before) / 2.0;
+            (before < position && position < after).then_some(position)
+        }
+    }
+}
+
+/// Appends the SQL of a filter condition, with every value bound as a
+/// parameter.
+fn push_condition(
+    query: &mut QueryBuilder<'_, Sqlite>,
+    condition: &Condition,
+    user_id: Uuid,
+    today: NaiveDate,
+    now: DateTime<Utc>,
+) {
+    match condition {
+        Condition::Title(text) => {
+            let escaped = text
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            query.push("tasks.title LIKE ");
+            query.push_bind(format!("%{escaped}%"));
+            query.push(" ESCAPE '\\'");
+        }
+        Condition::Completed => {
+            query.push("tasks.completed_at IS NOT NULL");
+        }
+        Condition::Overdue => {
+            query.push("tasks.completed_at IS NULL AND (tasks.due_at < ");
+            query.push_bind(now);
+            query.push(" OR (tasks.due_at IS NULL AND tasks.due_date < ");
+            query.push_bind(today);
+            query.push("))");
+        }
+        Condition::NoDueDate => {
+            query.push("tasks.due_date IS NULL");
+        }
+        Condition::Due(comparison, day) => {
+            let date = match *day {
+                // Parsing bounds relative days, so this stays in range.
+                Day::Relative(days) => today + chrono::Duration::days(days),
+                Day::Date(date) => date,
+            };
+            query.push(format!("tasks.due_date {} ", comparison.as_sql()));
+            query.push_bind(date);
+        }
+        Condition::Label(name) => {
+            query.push(
+                "EXISTS (
+                    SELECT 1 FROM tasks_labels
+                    JOIN labels ON labels.id = tasks_labels.label_id
+                    WHERE tasks_labels.task_id = tasks.id
+                    AND labels.user_id = ",
+            );
+            query.push_bind(user_id);
+            query.push(" AND labels.name = ");
+            query.push_bind(name.clone());
+            query.push(" COLLATE NOCASE)");
+        }
+        Condition::List(title) => {
+            query.push("lists.title = ");
+            query.push_bind(title.clone());
+            query.push(" COLLATE NOCASE");
+        }
+        Condition::Priority(comparison, priority) => {
+            query.push(format!("tasks.priority {} ", comparison.as_sql()));
+            query.push_bind(*priority);
+        }
+    }
 }
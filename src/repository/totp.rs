@@ -0,0 +1,85 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::model::User;
+
+use super::error::Result;
+
+impl User {
+    /// Enables two-factor authentication with the secret and replaces the
+    /// recovery codes, or disables it and drops them when `secret` is `None`.
+    pub async fn set_totp(
+        &self,
+        secret: Option<&str>,
+        recovery_code_hashes: &[String],
+        pool: &SqlitePool,
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE users SET totp_secret = ?, totp_last_step = NULL
+            WHERE id = ?",
+            secret,
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM recovery_codes WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        for code_hash in recovery_code_hashes {
+            sqlx::query!(
+                "INSERT INTO recovery_codes (user_id, code_hash)
+                VALUES (?, ?)",
+                self.id,
+                code_hash
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Records the time step of an accepted TOTP code, returning whether it
+    /// is later than the last one so that codes can't be replayed.
+    pub async fn use_totp_step(&self, step: u64, pool: &SqlitePool) -> Result<bool> {
+        let step = step as i64;
+
+        let result = sqlx::query!(
+            "UPDATE users SET totp_last_step = ?
+            WHERE id = ?
+            AND (totp_last_step IS NULL OR totp_last_step < ?)",
+            step,
+            self.id,
+            step
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Marks the recovery code as used, returning whether it was still valid.
+    pub async fn use_recovery_code(&self, code_hash: &str, pool: &SqlitePool) -> Result<bool> {
+        let used_at = Utc::now();
+
+        let result = sqlx::query!(
+            "UPDATE recovery_codes SET used_at = ?
+            WHERE user_id = ?
+            AND code_hash = ?
+            AND used_at IS NULL",
+            used_at,
+            self.id,
+            code_hash
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
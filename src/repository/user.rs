@@ -1,20 +1,68 @@
+use std::collections::HashSet;
+
 use sqlx::SqlitePool;
+use uuid::Uuid;
 
 use crate::model::User;
+use crate::util::password_hash::{hash_password, needs_rehash, verify_password};
+use crate::util::validation::{normalize_username, USERNAME_MAX_LENGTH};
 
 use super::error::{Error, Result};
 
 impl User {
-    pub async fn insert_user(&self, pool: &SqlitePool) -> Result<()> {
-        if sqlx::query!("SELECT * FROM users WHERE username = ?", self.username)
-            .fetch_optional(pool)
-            .await?
-            .is_some()
-        {
-            return Err(Error::UserAlreadyExists);
+    /// Normalizes the usernames stored before they had to be unique, the way
+    /// login input is normalized, renaming all but the oldest of colliding
+    /// ones with a random suffix. Does nothing once the unique index exists,
+    /// so it must run before the migrations.
+    pub async fn normalize_usernames(pool: &SqlitePool) -> Result<()> {
+        let pending: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'users')
+            AND NOT EXISTS (
+                SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = 'users_username'
+            )",
+        )
+        .fetch_one(pool)
+        .await?;
+
+        if !pending {
+            return Ok(());
         }
 
-        sqlx::query!(
+        let mut tx = pool.begin().await?;
+
+        // The table may predate the columns the macros know about, so only the
+        // columns of the initial schema are read.
+        let users: Vec<(Vec<u8>, String)> =
+            sqlx::query_as("SELECT id, username FROM users ORDER BY rowid")
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let mut taken = HashSet::new();
+        for (id, username) in users {
+            let mut normalized = normalize_username(&username);
+            if !taken.insert(normalized.clone()) {
+                let suffix = Uuid::new_v4().simple().to_string();
+                let prefix: String = normalized.chars().take(USERNAME_MAX_LENGTH - 9).collect();
+                normalized = format!("{prefix}-{}", &suffix[..8]);
+                taken.insert(normalized.clone());
+            }
+
+            if normalized != username {
+                sqlx::query("UPDATE users SET username = ? WHERE id = ?")
+                    .bind(normalized)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn insert_user(&self, pool: &SqlitePool) -> Result<()> {
+        let result = sqlx::query!(
             "INSERT INTO users (id, username, password_hash)
             VALUES (?, ?, ?)",
             self.id,
@@ -22,9 +70,15 @@ impl User {
             self.password_hash
         )
         .execute(pool)
-        .await?;
+        .await;
 
-        Ok(())
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                Err(Error::UserAlreadyExists)
+            }
+            Err(error) => Err(error.into()),
+        }
     }
 
     pub async fn get_user_by_username(username: &str, pool: &SqlitePool) -> Result<Option<User>> {
@@ -33,7 +87,11 @@ impl User {
             r#"SELECT
                 id as "id: _",
                 username,
-                password_hash
+                password_hash,
+                password_needs_rehash as "password_needs_rehash: bool",
+                totp_secret,
+                email,
+                time_zone
             FROM users
             WHERE username = ?"#,
             username
@@ -43,4 +101,219 @@ impl User {
 
         Ok(user)
     }
+
+    pub async fn get_user_by_id(id: Uuid, pool: &SqlitePool) -> Result<Option<User>> {
+        let user = sqlx::query_as!(
+            User,
+            r#"SELECT
+                id as "id: _",
+                username,
+                password_hash,
+                password_needs_rehash as "password_needs_rehash: bool",
+                totp_secret,
+                email,
+                time_zone
+            FROM users
+            WHERE id = ?"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    pub async fn get_user_by_email(email: &str, pool: &SqlitePool) -> Result<Option<User>> {
+        let user = sqlx::query_as!(
+            User,
+            r#"SELECT
+                id as "id: _",
+                username,
+                password_hash,
+                password_needs_rehash as "password_needs_rehash: bool",
+                totp_secret,
+                email,
+                time_zone
+            FROM users
+            WHERE email = ?"#,
+            email
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Checks the password, rehashing and storing it when the current hash
+    /// was made with weaker parameters or the legacy shared salt.
+    pub async fn verify_password(&mut self, password: &str, pool: &SqlitePool) -> Result<bool> {
+        if !verify_password(password, &self.password_hash) {
+            return Ok(false);
+        }
+
+        if self.password_needs_rehash || needs_rehash(&self.password_hash) {
+            self.password_hash = hash_password(password);
+            self.password_needs_rehash = false;
+            self.update_password_hash(pool).await?;
+        }
+
+        Ok(true)
+    }
+
+    pub async fn update_password_hash(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "UPDATE users SET password_hash = ?, password_needs_rehash = 0
+            WHERE id = ?",
+            self.password_hash,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stores the email address, which must not belong to anyone else.
+    pub async fn update_email(&self, pool: &SqlitePool) -> Result<()> {
+        let result = sqlx::query!(
+            "UPDATE users SET email = ?
+            WHERE id = ?",
+            self.email,
+            self.id
+        )
+        .execute(pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                Err(Error::EmailAlreadyUsed)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub async fn update_time_zone(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query!(
+            "UPDATE users SET time_zone = ?
+            WHERE id = ?",
+            self.time_zone,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the user along with their sessions and memberships. Each list
+    /// they own is handed over to its most privileged member, or deleted with
+    /// its tasks and invites when nobody else has access to it.
+    pub async fn delete_user(&self, pool: &SqlitePool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE lists SET created_by = (
+                SELECT user_id FROM lists_users
+                WHERE list_id = lists.id
+                ORDER BY CASE role
+                    WHEN 'owner' THEN 0
+                    WHEN 'editor' THEN 1
+                    ELSE 2
+                END
+                LIMIT 1
+            )
+            WHERE created_by = ?
+            AND EXISTS (SELECT 1 FROM lists_users WHERE list_id = lists.id)",
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // New owners no longer need a membership on the lists they inherited.
+        sqlx::query!(
+            "DELETE FROM lists_users
+            WHERE user_id = (SELECT created_by FROM lists WHERE id = lists_users.list_id)"
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM tasks_labels
+            WHERE task_id IN (
+                SELECT tasks.id FROM tasks
+                JOIN lists ON lists.id = tasks.list_id
+                WHERE lists.created_by = ?
+            )
+            OR label_id IN (SELECT id FROM labels WHERE user_id = ?)",
+            self.id,
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM labels WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM saved_filters WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            "DELETE FROM tasks
+            WHERE list_id IN (SELECT id FROM lists WHERE created_by = ?)",
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM invitations
+            WHERE list_id IN (SELECT id FROM lists WHERE created_by = ?)",
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM lists WHERE created_by = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM lists_users WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM user_identities WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM api_tokens WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM password_resets WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM email_confirmations WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM recovery_codes WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM sessions WHERE user_id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM users WHERE id = ?", self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
 }
@@ -1,4 +1,6 @@
+#[cfg(feature = "server")]
 use chrono::{DateTime, Utc};
+use chrono::{NaiveDate, NaiveTime};
 use dioxus::prelude::*;
 #[cfg(feature = "server")]
 use sqlx::SqlitePool;
@@ -6,9 +8,39 @@ use sqlx::SqlitePool;
 use tower_sessions::Session;
 use uuid::Uuid;
 
+use crate::model::{
+    ActiveSession, ApiToken, Invite, Label, List, Member, Priority, Role, SavedFilter, Scope,
+    SearchResult, SmartView, Task, TaskLabel, TaskNode, TotpEnrollment, User,
+};
+#[cfg(feature = "server")]
+use crate::model::{EmailConfirmation, Identity, PasswordReset};
+#[cfg(feature = "server")]
+use crate::repository::error::Error;
+#[cfg(feature = "server")]
+use crate::repository::session::SqliteStore;
+#[cfg(feature = "server")]
+use crate::util::filter::Filter;
+#[cfg(feature = "server")]
+use crate::util::mail;
+#[cfg(feature = "server")]
+use crate::util::oidc;
+#[cfg(feature = "server")]
+use crate::util::password_hash::hash_password;
 #[cfg(feature = "server")]
-use crate::model::User;
-use crate::model::{List, Task};
+use crate::util::rate_limit::RateLimiter;
+#[cfg(feature = "server")]
+use crate::util::recurrence::Recurrence;
+#[cfg(feature = "server")]
+use crate::util::time_zone;
+#[cfg(feature = "server")]
+use crate::util::token::{generate_token, hash_token};
+#[cfg(feature = "server")]
+use crate::util::totp;
+#[cfg(feature = "server")]
+use crate::util::validation::{
+    normalize_email, normalize_username, sanitize_username, validate_email, validate_password,
+    validate_registration, Field, FieldError, FieldErrors, USERNAME_MAX_LENGTH,
+};
 
 #[cfg(feature = "server")]
 #[doc(hidden)]
@@ -26,35 +58,377 @@ macro_rules! pool {
     };
 }
 
+/// Resolves the user logged in with the session, if any.
+#[cfg(feature = "server")]
+async fn user_from_session() -> Result<Option<User>, ServerFnError> {
+    let session = session!().await.unwrap();
+    let Some(user) = session.get::<User>("user").await? else {
+        return Ok(None);
+    };
+
+    if let Some(info) = session.get::<ActiveSession>("info").await? {
+        let pool = pool!().await?;
+        ActiveSession::touch_session(info.id, &pool).await?;
+    }
+
+    Ok(Some(user))
+}
+
+/// Resolves the user from the session only, ignoring any API token, for
+/// functions managing credentials, email, two-factor authentication, tokens
+/// and sessions.
+#[cfg(feature = "server")]
+async fn authenticate_session() -> Result<User, ServerFnError> {
+    let user = user_from_session().await?.ok_or(Error::Unauthorized)?;
+    Ok(user)
+}
+
+/// Resolves the user from the session, or from an `Authorization: Bearer`
+/// API token with at least the `required` scope.
+#[cfg(feature = "server")]
+async fn authenticate(required: Scope) -> Result<User, ServerFnError> {
+    use axum::http::header::AUTHORIZATION;
+    use axum::http::HeaderMap;
+
+    if let Some(user) = user_from_session().await? {
+        return Ok(user);
+    }
+
+    let headers: HeaderMap = extract().await?;
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(Error::Unauthorized)?;
+
+    let pool = pool!().await?;
+    let token = ApiToken::use_api_token(&hash_token(token.trim()), &pool)
+        .await?
+        .ok_or(Error::Unauthorized)?;
+
+    if token.scope < required {
+        return Err(Error::Forbidden.into());
+    }
+
+    let user = User::get_user_by_id(token.user_id, &pool)
+        .await?
+        .ok_or(Error::Unauthorized)?;
+
+    Ok(user)
+}
+
+/// Logs the user in on a fresh session id, recording which client it is for
+/// the active sessions page.
+#[cfg(feature = "server")]
+async fn start_session(session: &Session, user: &User) -> Result<(), ServerFnError> {
+    use std::net::SocketAddr;
+
+    use axum::extract::ConnectInfo;
+    use axum::http::header::USER_AGENT;
+    use axum::http::HeaderMap;
+
+    let headers: HeaderMap = extract().await?;
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let ip = extract::<ConnectInfo<SocketAddr>, _>()
+        .await
+        .ok()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+    session.cycle_id().await?;
+    session.insert("user", user).await?;
+    session
+        .insert("info", ActiveSession::new(user_agent, ip))
+        .await?;
+
+    Ok(())
+}
+
+/// `user!()` requires write access, `user!(read)` is enough for functions
+/// that don't modify anything.
+#[cfg(feature = "server")]
+#[doc(hidden)]
+macro_rules! user {
+    () => {
+        authenticate(Scope::Write)
+    };
+    (read) => {
+        authenticate(Scope::Read)
+    };
+}
+
+/// `session_user!()` only accepts a logged in session, so that an API token
+/// can't be used to take over the account.
+#[cfg(feature = "server")]
+#[doc(hidden)]
+macro_rules! session_user {
+    () => {
+        authenticate_session()
+    };
+}
+
+/// Resolves a task from the database rather than trusting the client copy,
+/// and checks that the user has at least the `required` role on its list.
+#[cfg(feature = "server")]
+async fn authorize_task(
+    task_id: Uuid,
+    user: &User,
+    required: Role,
+    pool: &SqlitePool,
+) -> Result<Task, Error> {
+    let task = Task::get_task(task_id, pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    List::authorize(task.list_id, user.id, required, pool).await?;
+    Ok(task)
+}
+
+#[cfg(feature = "server")]
+static LOGIN_LIMITER: std::sync::LazyLock<RateLimiter> =
+    std::sync::LazyLock::new(RateLimiter::default);
+
+/// Logs in with a password. Returns `None` when the account has two-factor
+/// authentication enabled, in which case [`login_totp`] must follow.
+#[server]
+pub async fn login(username: String, password: String) -> Result<Option<User>, ServerFnError> {
+    use std::net::SocketAddr;
+    use std::time::Instant;
+
+    use axum::extract::ConnectInfo;
+
+    let pool = pool!().await?;
+    let session = session!().await.unwrap();
+
+    let now = Instant::now();
+    let mut keys = vec![format!("user:{}", normalize_username(&username))];
+    if let Ok(ConnectInfo(addr)) = extract::<ConnectInfo<SocketAddr>, _>().await {
+        keys.push(format!("ip:{}", addr.ip()));
+    }
+
+    if let Some(wait) = keys
+        .iter()
+        .filter_map(|key| LOGIN_LIMITER.check(key, now))
+        .max()
+    {
+        return Err(Error::TooManyAttempts(wait.as_secs().max(1)).into());
+    }
+
+    let username = normalize_username(&username);
+
+    let verified = match User::get_user_by_username(&username, &pool).await? {
+        Some(mut user) => user
+            .verify_password(&password, &pool)
+            .await?
+            .then_some(user),
+        None => {
+            // Spend as long as a real check so timing doesn't reveal whether
+            // the username exists.
+            hash_password(&password);
+            None
+        }
+    };
+
+    let Some(user) = verified else {
+        for key in &keys {
+            LOGIN_LIMITER.record_failure(key, now);
+        }
+        return Err(Error::InvalidCredentials.into());
+    };
+
+    for key in &keys {
+        LOGIN_LIMITER.record_success(key);
+    }
+
+    if user.totp_secret.is_some() {
+        session
+            .insert("pending_user", (user.id, Utc::now()))
+            .await?;
+        return Ok(None);
+    }
+
+    start_session(&session, &user).await?;
+
+    Ok(Some(user))
+}
+
+/// Completes a [`login`] with a TOTP code or an unused recovery code.
 #[server]
-pub async fn login(username: String, password: String) -> Result<(), ServerFnError> {
+pub async fn login_totp(code: String) -> Result<User, ServerFnError> {
+    use std::time::Instant;
+
     let pool = pool!().await?;
     let session = session!().await.unwrap();
 
-    let user = match User::get_user_by_username(&username, &pool).await? {
-        Some(user) => user,
-        None => return Err(ServerFnError::new("User not found")),
+    let (user_id, started_at) = session
+        .get::<(Uuid, DateTime<Utc>)>("pending_user")
+        .await?
+        .ok_or(Error::Unauthorized)?;
+
+    if Utc::now() - started_at > chrono::Duration::minutes(5) {
+        session.remove_value("pending_user").await?;
+        return Err(Error::Unauthorized.into());
+    }
+
+    let now = Instant::now();
+    let key = format!("totp:{user_id}");
+
+    if let Some(wait) = LOGIN_LIMITER.check(&key, now) {
+        return Err(Error::TooManyAttempts(wait.as_secs().max(1)).into());
+    }
+
+    let user = User::get_user_by_id(user_id, &pool)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    let secret = user.totp_secret.as_deref().ok_or(Error::Unauthorized)?;
+
+    let valid = match totp::verify(secret, &code, Utc::now().timestamp() as u64) {
+        Some(step) => user.use_totp_step(step, &pool).await?,
+        None => {
+            user.use_recovery_code(&totp::hash_recovery_code(&code), &pool)
+                .await?
+        }
     };
 
-    if !user.verify_password(&password) {
-        return Err(ServerFnError::new("Invalid password"));
+    if !valid {
+        LOGIN_LIMITER.record_failure(&key, now);
+        return Err(Error::InvalidCredentials.into());
     }
 
-    session.insert("user", user).await?;
+    LOGIN_LIMITER.record_success(&key);
 
-    Ok(())
+    session.remove_value("pending_user").await?;
+    start_session(&session, &user).await?;
+
+    Ok(user)
+}
+
+#[server]
+pub async fn oidc_enabled() -> Result<bool, ServerFnError> {
+    Ok(oidc::CONFIG.is_some())
+}
+
+/// Returns the identity provider URL to send the user to.
+#[server]
+pub async fn oidc_login_url() -> Result<String, ServerFnError> {
+    let session = session!().await.unwrap();
+
+    let config = oidc::CONFIG
+        .as_ref()
+        .ok_or(ServerFnError::new("OIDC is not configured"))?;
+
+    let (url, pending) = oidc::authorization_url(config).await?;
+    session.insert("oidc", &pending).await?;
+
+    Ok(url)
+}
+
+/// Creates a user for a subject logging in for the first time. Their password
+/// is random and never shown, so they can only log in through the provider.
+#[cfg(feature = "server")]
+async fn provision_user(claims: &oidc::Claims, pool: &SqlitePool) -> Result<User, Error> {
+    let username = sanitize_username(
+        claims
+            .preferred_username
+            .as_ref()
+            .or(claims.email.as_ref())
+            .unwrap_or(&claims.sub),
+    );
+
+    let user = User::new(username.clone(), generate_token());
+    match user.insert_user(pool).await {
+        Err(Error::UserAlreadyExists) => {
+            let suffix = Uuid::new_v4().simple().to_string();
+            let username: String = username
+                .chars()
+                .take(USERNAME_MAX_LENGTH - suffix[..8].len() - 1)
+                .collect();
+            let user = User::new(format!("{username}-{}", &suffix[..8]), generate_token());
+            user.insert_user(pool).await?;
+            Ok(user)
+        }
+        result => result.map(|_| user),
+    }
+}
+
+/// Completes an OpenID Connect login. When someone is already logged in, the
+/// identity is linked to their account instead.
+#[server]
+pub async fn oidc_callback(code: String, state: String) -> Result<User, ServerFnError> {
+    let pool = pool!().await?;
+    let session = session!().await.unwrap();
+
+    let config = oidc::CONFIG
+        .as_ref()
+        .ok_or(ServerFnError::new("OIDC is not configured"))?;
+
+    let pending = session
+        .remove::<oidc::Pending>("oidc")
+        .await?
+        .ok_or(Error::Unauthorized)?;
+
+    if pending.state != state {
+        return Err(Error::Unauthorized.into());
+    }
+
+    let claims = oidc::exchange_code(config, &code, &pending).await?;
+
+    let current = session.get::<User>("user").await?;
+    let user = identity_user(config, claims, current, &pool).await?;
+    start_session(&session, &user).await?;
+
+    Ok(user)
+}
+
+/// The user the identity is linked to. An unknown identity is linked to the
+/// `current` logged in user, or else to a new user when auto provisioning.
+#[cfg(feature = "server")]
+async fn identity_user(
+    config: &oidc::Config,
+    claims: oidc::Claims,
+    current: Option<User>,
+    pool: &SqlitePool,
+) -> Result<User, Error> {
+    if let Some(identity) = Identity::get_identity(&config.issuer, &claims.sub, pool).await? {
+        return User::get_user_by_id(identity.user_id, pool)
+            .await?
+            .ok_or(Error::UserNotFound);
+    }
+
+    let user = match current {
+        Some(user) => user,
+        None if config.auto_provision => provision_user(&claims, pool).await?,
+        None => return Err(Error::UserNotFound),
+    };
+
+    let identity = Identity::new(config.issuer.clone(), claims.sub, user.id);
+    identity.insert_identity(pool).await?;
+
+    Ok(user)
 }
 
+/// Registers a user. Invalid or taken usernames and weak passwords fail with
+/// [`FieldErrors`] as the error message.
 #[server]
-pub async fn register(username: String, password: String) -> Result<(), ServerFnError> {
+pub async fn register(username: String, password: String) -> Result<User, ServerFnError> {
     let pool = pool!().await?;
     let session = session!().await.unwrap();
 
+    let username = validate_registration(&username, &password).map_err(ServerFnError::new)?;
+
     let user = User::new(username, password);
-    user.insert_user(&pool).await?;
-    session.insert("user", user).await?;
+    match user.insert_user(&pool).await {
+        Err(Error::UserAlreadyExists) => {
+            let errors = FieldErrors(vec![FieldError::new(Field::Username, "Already taken")]);
+            return Err(ServerFnError::new(errors));
+        }
+        result => result?,
+    }
 
-    Ok(())
+    start_session(&session, &user).await?;
+
+    Ok(user)
 }
 
 #[server]
@@ -64,72 +438,1351 @@ pub async fn logout() -> Result<(), ServerFnError> {
     Ok(())
 }
 
+/// Logs out every session of the user, this one included.
 #[server]
-pub async fn create_list(title: String) -> Result<(), ServerFnError> {
-    let session = session!().await.unwrap();
+pub async fn logout_everywhere() -> Result<(), ServerFnError> {
     let pool = pool!().await?;
+    let session = session!().await.unwrap();
+    let user = session_user!().await?;
 
-    let user: User = session.get("user").await.unwrap().unwrap();
-    let list = List::new(title, user.id);
+    SqliteStore::new(pool)
+        .delete_user_sessions(user.id, None)
+        .await?;
+    session.delete().await?;
 
-    list.create_list(&pool).await?;
     Ok(())
 }
 
 #[server]
-pub async fn get_lists() -> Result<Vec<List>, ServerFnError> {
+pub async fn get_sessions() -> Result<Vec<ActiveSession>, ServerFnError> {
     let pool = pool!().await?;
     let session = session!().await.unwrap();
+    let user = session_user!().await?;
 
-    let user: User = session.get("user").await.unwrap().unwrap();
-    let lists = List::get_user_lists(user.id, &pool).await?;
+    let current = session
+        .get::<ActiveSession>("info")
+        .await?
+        .map(|info| info.id);
 
-    Ok(lists)
+    let sessions = ActiveSession::get_user_sessions(user.id, &pool)
+        .await?
+        .into_iter()
+        .map(|active_session| ActiveSession {
+            current: Some(active_session.id) == current,
+            ..active_session
+        })
+        .collect();
+
+    Ok(sessions)
 }
 
 #[server]
-pub async fn delete_list(list: List) -> Result<(), ServerFnError> {
+pub async fn revoke_session(id: Uuid) -> Result<(), ServerFnError> {
     let pool = pool!().await?;
-    list.delete_list(&pool).await?;
+    let user = session_user!().await?;
+
+    ActiveSession::delete_session(id, user.id, &pool).await?;
     Ok(())
 }
 
+/// Returns the user logged in with the current session, if any.
 #[server]
-pub async fn create_task(
-    title: String,
-    due_date: DateTime<Utc>,
-    list_id: Uuid,
-) -> Result<(), ServerFnError> {
+pub async fn current_user() -> Result<Option<User>, ServerFnError> {
     let session = session!().await.unwrap();
+    let user = session.get::<User>("user").await?;
+    Ok(user)
+}
+
+/// Loads the session user with their password hash, which the session itself
+/// never stores, and checks the given password against it.
+#[cfg(feature = "server")]
+async fn reauthenticate(password: &str, pool: &SqlitePool) -> Result<User, ServerFnError> {
+    let user = session_user!().await?;
+
+    let mut user = User::get_user_by_id(user.id, pool)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    if !user.verify_password(password, pool).await? {
+        return Err(Error::InvalidCredentials.into());
+    }
+
+    Ok(user)
+}
+
+#[server]
+pub async fn change_password(
+    current_password: String,
+    new_password: String,
+) -> Result<(), ServerFnError> {
     let pool = pool!().await?;
+    let session = session!().await.unwrap();
 
-    let user: User = session.get("user").await.unwrap().unwrap();
-    let task = Task::new(title, due_date, list_id, user.id);
+    let mut user = reauthenticate(&current_password, &pool).await?;
+
+    let errors = validate_password(&new_password, &user.username);
+    if !errors.is_empty() {
+        return Err(ServerFnError::new(FieldErrors(errors)));
+    }
+
+    user.password_hash = hash_password(&new_password);
+    user.update_password_hash(&pool).await?;
+
+    // Log out every session, then keep this one alive under a fresh id.
+    SqliteStore::new(pool)
+        .delete_user_sessions(user.id, None)
+        .await?;
+    session.cycle_id().await?;
 
-    task.create_task(&pool).await?;
     Ok(())
 }
 
+/// Mails a link to confirm the address password reset links should be sent
+/// to, which is only stored once [`confirm_email`] is called with it, and
+/// returns the user unchanged. An empty address removes the current one
+/// right away. Invalid or already used addresses fail with [`FieldErrors`]
+/// as the error message.
 #[server]
-pub async fn get_tasks(list_id: Uuid) -> Result<Vec<Task>, ServerFnError> {
+pub async fn set_email(email: String) -> Result<User, ServerFnError> {
+    use std::time::Instant;
+
     let pool = pool!().await?;
-    let tasks = Task::get_list_tasks(list_id, &pool).await?;
-    Ok(tasks)
+    let session = session!().await.unwrap();
+    let user = session_user!().await?;
+
+    let email = normalize_email(&email);
+    if email.is_empty() {
+        let user = User {
+            email: None,
+            ..user
+        };
+        user.update_email(&pool).await?;
+        session.insert("user", &user).await?;
+        return Ok(user);
+    }
+
+    let errors = validate_email(&email);
+    if !errors.is_empty() {
+        return Err(ServerFnError::new(FieldErrors(errors)));
+    }
+
+    if user.email.as_ref() == Some(&email) {
+        return Ok(user);
+    }
+
+    if User::get_user_by_email(&email, &pool)
+        .await?
+        .is_some_and(|other| other.id != user.id)
+    {
+        let errors = FieldErrors(vec![FieldError::new(
+            Field::Email,
+            "Already used by another account",
+        )]);
+        return Err(ServerFnError::new(errors));
+    }
+
+    let mailer = mail::MAILER
+        .as_ref()
+        .ok_or(ServerFnError::new("Outgoing mail is not configured"))?;
+
+    // Every request counts as a failure so nobody can flood an inbox.
+    let now = Instant::now();
+    let key = format!("confirm:{}", user.id);
+
+    if let Some(wait) = LOGIN_LIMITER.check(&key, now) {
+        return Err(Error::TooManyAttempts(wait.as_secs().max(1)).into());
+    }
+    LOGIN_LIMITER.record_failure(&key, now);
+
+    let (email_confirmation, token) =
+        EmailConfirmation::new(user.id, email.clone(), chrono::Duration::days(1));
+    email_confirmation.create_email_confirmation(&pool).await?;
+
+    mailer
+        .send_email_confirmation(&email, &user.username, &token)
+        .await
+        .map_err(|error| ServerFnError::new(format!("Failed to send mail: {error}")))?;
+
+    Ok(user)
 }
 
+/// Stores the address with a token from [`set_email`], whoever opens the
+/// link, updating the session if it belongs to the same user.
 #[server]
-pub async fn complete_task(task: Task) -> Result<(), ServerFnError> {
+pub async fn confirm_email(token: String) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let session = session!().await.unwrap();
+
+    let email_confirmation =
+        EmailConfirmation::get_valid_email_confirmation(&hash_token(token.trim()), &pool)
+            .await?
+            .ok_or(Error::InvalidEmailConfirmation)?;
+
+    let user = User::get_user_by_id(email_confirmation.user_id, &pool)
+        .await?
+        .ok_or(Error::InvalidEmailConfirmation)?;
+
+    if !email_confirmation.use_email_confirmation(&pool).await? {
+        return Err(Error::InvalidEmailConfirmation.into());
+    }
+
+    let user = User {
+        email: Some(email_confirmation.email),
+        ..user
+    };
+    user.update_email(&pool).await?;
+
+    if session
+        .get::<User>("user")
+        .await?
+        .is_some_and(|session_user| session_user.id == user.id)
+    {
+        session.insert("user", &user).await?;
+    }
+
+    Ok(())
+}
+
+#[server]
+pub async fn set_time_zone(time_zone: String) -> Result<User, ServerFnError> {
+    let pool = pool!().await?;
     let session = session!().await.unwrap();
+    let user = user!().await?;
+
+    let tz =
+        time_zone::parse_time_zone(&time_zone).ok_or(ServerFnError::new("Unknown time zone"))?;
+
+    let user = User {
+        time_zone: tz.name().to_owned(),
+        ..user
+    };
+    user.update_time_zone(&pool).await?;
+
+    if session.get::<User>("user").await?.is_some() {
+        session.insert("user", &user).await?;
+    }
+
+    Ok(user)
+}
+
+#[server]
+pub async fn password_reset_enabled() -> Result<bool, ServerFnError> {
+    Ok(mail::MAILER.is_some())
+}
+
+/// Mails a password reset link to the user with the given username or email
+/// address. Succeeds whether or not there is such a user with an email
+/// address, so it can't be used to find out who has an account.
+#[server]
+pub async fn request_password_reset(login: String) -> Result<(), ServerFnError> {
+    use std::time::Instant;
+
+    use dioxus::logger::tracing;
+
+    let pool = pool!().await?;
+
+    let mailer = mail::MAILER
+        .as_ref()
+        .ok_or(ServerFnError::new("Password reset is not configured"))?;
+
+    let user = if login.contains('@') {
+        User::get_user_by_email(&normalize_email(&login), &pool).await?
+    } else {
+        User::get_user_by_username(&normalize_username(&login), &pool).await?
+    };
+
+    let Some((user, email)) = user.and_then(|user| {
+        let email = user.email.clone()?;
+        Some((user, email))
+    }) else {
+        return Ok(());
+    };
+
+    // Every request counts as a failure so nobody can flood an inbox. Going
+    // over the limit is silent, as an error would reveal the account.
+    let now = Instant::now();
+    let key = format!("reset:{}", user.id);
+
+    if LOGIN_LIMITER.check(&key, now).is_some() {
+        return Ok(());
+    }
+    LOGIN_LIMITER.record_failure(&key, now);
+
+    let (password_reset, token) = PasswordReset::new(user.id, chrono::Duration::hours(1));
+    password_reset.create_password_reset(&pool).await?;
+
+    // Sent in the background so that known and unknown accounts answer
+    // equally fast.
+    let username = user.username;
+    tokio::spawn(async move {
+        if let Err(error) = mailer.send_password_reset(&email, &username, &token).await {
+            tracing::error!("Failed to send password reset mail: {error}");
+        }
+    });
+
+    Ok(())
+}
+
+/// Sets a new password with a token from [`request_password_reset`] and
+/// logs out every session of the user. Weak passwords fail with
+/// [`FieldErrors`] as the error message, without using up the token.
+#[server]
+pub async fn reset_password(token: String, password: String) -> Result<(), ServerFnError> {
     let pool = pool!().await?;
 
-    let user: User = session.get("user").await.unwrap().unwrap();
-    task.complete_task(user.id, &pool).await?;
+    let password_reset = PasswordReset::get_valid_password_reset(&hash_token(token.trim()), &pool)
+        .await?
+        .ok_or(Error::InvalidPasswordReset)?;
+
+    let mut user = User::get_user_by_id(password_reset.user_id, &pool)
+        .await?
+        .ok_or(Error::InvalidPasswordReset)?;
+
+    let errors = validate_password(&password, &user.username);
+    if !errors.is_empty() {
+        return Err(ServerFnError::new(FieldErrors(errors)));
+    }
+
+    if !password_reset.use_password_reset(&pool).await? {
+        return Err(Error::InvalidPasswordReset.into());
+    }
+
+    user.password_hash = hash_password(&password);
+    user.update_password_hash(&pool).await?;
+
+    SqliteStore::new(pool)
+        .delete_user_sessions(user.id, None)
+        .await?;
+
     Ok(())
 }
 
 #[server]
-pub async fn uncomplete_task(task: Task) -> Result<(), ServerFnError> {
+pub async fn delete_account(password: String) -> Result<(), ServerFnError> {
     let pool = pool!().await?;
-    task.uncomplete_task(&pool).await?;
+    let session = session!().await.unwrap();
+
+    let user = reauthenticate(&password, &pool).await?;
+
+    user.delete_user(&pool).await?;
+    session.delete().await?;
+
     Ok(())
 }
+
+#[server]
+pub async fn totp_enabled() -> Result<bool, ServerFnError> {
+    let pool = pool!().await?;
+    let user = session_user!().await?;
+
+    let user = User::get_user_by_id(user.id, &pool)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    Ok(user.totp_secret.is_some())
+}
+
+/// Loads the user, refusing to enroll over an existing secret: replacing it
+/// must go through [`disable_totp`], which asks for the password.
+#[cfg(feature = "server")]
+async fn user_without_totp(user_id: Uuid, pool: &SqlitePool) -> Result<User, Error> {
+    let user = User::get_user_by_id(user_id, pool)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    match user.totp_secret {
+        Some(_) => Err(Error::TotpAlreadyEnabled),
+        None => Ok(user),
+    }
+}
+
+/// Generates a new secret, kept in the session until
+/// [`confirm_totp_enrollment`] proves the authenticator app has it.
+#[server]
+pub async fn start_totp_enrollment() -> Result<TotpEnrollment, ServerFnError> {
+    let pool = pool!().await?;
+    let session = session!().await.unwrap();
+    let user = session_user!().await?;
+    let user = user_without_totp(user.id, &pool).await?;
+
+    let secret = totp::generate_secret();
+    let uri = totp::uri(&secret, "todo", &user.username);
+    let qr_code = totp::qr_code(&uri);
+
+    session.insert("totp_enrollment", &secret).await?;
+
+    Ok(TotpEnrollment {
+        secret,
+        uri,
+        qr_code,
+    })
+}
+
+/// Enables two-factor authentication and returns the recovery codes, which
+/// are only ever shown this once.
+#[server]
+pub async fn confirm_totp_enrollment(code: String) -> Result<Vec<String>, ServerFnError> {
+    let pool = pool!().await?;
+    let session = session!().await.unwrap();
+    let user = session_user!().await?;
+    let user = user_without_totp(user.id, &pool).await?;
+
+    let secret = session
+        .get::<String>("totp_enrollment")
+        .await?
+        .ok_or(ServerFnError::new("No enrollment in progress"))?;
+
+    let step = totp::verify(&secret, &code, Utc::now().timestamp() as u64)
+        .ok_or(Error::InvalidCredentials)?;
+
+    let recovery_codes = totp::generate_recovery_codes();
+    let recovery_code_hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| totp::hash_recovery_code(code))
+        .collect();
+
+    user.set_totp(Some(&secret), &recovery_code_hashes, &pool)
+        .await?;
+    // The code used to enroll can't be used to log in.
+    user.use_totp_step(step, &pool).await?;
+    session.remove_value("totp_enrollment").await?;
+
+    Ok(recovery_codes)
+}
+
+#[server]
+pub async fn disable_totp(password: String) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+
+    let user = reauthenticate(&password, &pool).await?;
+    user.set_totp(None, &[], &pool).await?;
+
+    Ok(())
+}
+
+#[server]
+pub async fn get_api_tokens() -> Result<Vec<ApiToken>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = session_user!().await?;
+
+    let tokens = ApiToken::get_user_api_tokens(user.id, &pool).await?;
+    Ok(tokens)
+}
+
+/// Creates an API token and returns its secret, which is only shown once.
+#[server]
+pub async fn create_api_token(name: String, scope: Scope) -> Result<String, ServerFnError> {
+    let pool = pool!().await?;
+    let user = session_user!().await?;
+
+    let (api_token, token) = ApiToken::new(name, scope, user.id);
+
+    api_token.create_api_token(&pool).await?;
+    Ok(token)
+}
+
+#[server]
+pub async fn revoke_api_token(api_token: ApiToken) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = session_user!().await?;
+
+    let api_token = ApiToken {
+        user_id: user.id,
+        ..api_token
+    };
+
+    api_token.delete_api_token(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn create_list(title: String) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let list = List::new(title, user.id);
+
+    list.create_list(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn get_lists() -> Result<Vec<List>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let lists = List::get_user_lists(user.id, &pool).await?;
+
+    Ok(lists)
+}
+
+#[server]
+pub async fn delete_list(list: List) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    List::authorize(list.id, user.id, Role::Owner, &pool).await?;
+    list.delete_list(&pool).await?;
+    Ok(())
+}
+
+/// Gives the list the title of the client copy.
+#[server]
+pub async fn rename_list(list: List) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let title = list.title.trim().to_owned();
+    if title.is_empty() {
+        return Err(ServerFnError::new("Title cannot be empty"));
+    }
+
+    List::authorize(list.id, user.id, Role::Owner, &pool).await?;
+
+    let list = List { title, ..list };
+    list.rename_list(&pool).await?;
+    Ok(())
+}
+
+/// Deleted lists the user owns, which are purged for good once the
+/// retention window has passed.
+#[server]
+pub async fn get_deleted_lists() -> Result<Vec<List>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let lists = List::get_deleted_lists(user.id, &pool).await?;
+    Ok(lists)
+}
+
+#[server]
+pub async fn restore_list(list_id: Uuid) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let list = List::get_deleted_list(list_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+
+    list.restore_list(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn purge_list(list_id: Uuid) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let list = List::get_deleted_list(list_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+
+    list.purge_list(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn get_role(list_id: Uuid) -> Result<Role, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let role = List::authorize(list_id, user.id, Role::Viewer, &pool).await?;
+    Ok(role)
+}
+
+#[server]
+pub async fn get_members(list_id: Uuid) -> Result<Vec<Member>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    List::authorize(list_id, user.id, Role::Viewer, &pool).await?;
+
+    let members = Member::get_list_members(list_id, &pool).await?;
+    Ok(members)
+}
+
+#[server]
+pub async fn share_list(list_id: Uuid, username: String, role: Role) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    List::authorize(list_id, user.id, Role::Owner, &pool).await?;
+
+    let member = match User::get_user_by_username(&normalize_username(&username), &pool).await? {
+        Some(member) if member.id != user.id => member,
+        Some(_) => return Err(ServerFnError::new("Cannot share a list with yourself")),
+        None => return Err(Error::UserNotFound.into()),
+    };
+
+    let member = Member {
+        list_id,
+        user_id: member.id,
+        username: member.username,
+        role,
+    };
+
+    member.insert_member(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn unshare_list(member: Member) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    List::authorize(member.list_id, user.id, Role::Owner, &pool).await?;
+    member.delete_member(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn create_invite(list_id: Uuid, role: Role) -> Result<String, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    List::authorize(list_id, user.id, Role::Owner, &pool).await?;
+
+    let invite = Invite::new(list_id, role, chrono::Duration::days(7), user.id);
+
+    invite.create_invite(&pool).await?;
+    Ok(invite.token())
+}
+
+#[server]
+pub async fn get_invites(list_id: Uuid) -> Result<Vec<(Invite, String)>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    List::authorize(list_id, user.id, Role::Owner, &pool).await?;
+
+    let invites = Invite::get_list_invites(list_id, &pool)
+        .await?
+        .into_iter()
+        .map(|invite| {
+            let token = invite.token();
+            (invite, token)
+        })
+        .collect();
+
+    Ok(invites)
+}
+
+#[server]
+pub async fn revoke_invite(invite: Invite) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let invite = Invite::get_valid_invite(invite.id, &pool)
+        .await?
+        .ok_or(Error::InvalidInvite)?;
+
+    List::authorize(invite.list_id, user.id, Role::Owner, &pool).await?;
+    invite.revoke_invite(&pool).await?;
+    Ok(())
+}
+
+/// Joins the list the invite was made for and returns its id.
+#[server]
+pub async fn redeem_invite(token: String) -> Result<Uuid, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let invite_id = crate::util::signature::verify(&token)
+        .and_then(|id| id.parse::<Uuid>().ok())
+        .ok_or(Error::InvalidInvite)?;
+
+    let invite = Invite::get_valid_invite(invite_id, &pool)
+        .await?
+        .ok_or(Error::InvalidInvite)?;
+
+    // Never downgrade a user who already has a higher role on the list.
+    match List::get_role(invite.list_id, user.id, &pool).await? {
+        Some(role) if role >= invite.role => {}
+        _ => {
+            let member = Member {
+                list_id: invite.list_id,
+                user_id: user.id,
+                username: user.username,
+                role: invite.role,
+            };
+
+            member.insert_member(&pool).await?;
+        }
+    }
+
+    Ok(invite.list_id)
+}
+
+/// Resolves a due date and time entered by the user into the stored due date
+/// and instant. A time needs a date to go with it.
+#[cfg(feature = "server")]
+fn resolve_due(
+    due_date: Option<NaiveDate>,
+    due_time: Option<NaiveTime>,
+    user: &User,
+) -> Result<(Option<NaiveDate>, Option<DateTime<Utc>>), ServerFnError> {
+    match (due_date, due_time) {
+        (Some(due_date), Some(due_time)) => {
+            let due_at = time_zone::to_utc(due_date, due_time, user.tz()).ok_or(
+                ServerFnError::new("This time doesn't exist on that day in your time zone"),
+            )?;
+            Ok((Some(due_date), Some(due_at)))
+        }
+        (due_date, None) => Ok((due_date, None)),
+        (None, Some(_)) => Err(ServerFnError::new("A due time needs a due date")),
+    }
+}
+
+/// Parses an RRULE into its canonical form. Recurring tasks repeat from
+/// their due date, so they need one.
+#[cfg(feature = "server")]
+fn resolve_recurrence(
+    recurrence: Option<String>,
+    due_date: Option<NaiveDate>,
+) -> Result<Option<String>, ServerFnError> {
+    let Some(recurrence) = recurrence.filter(|recurrence| !recurrence.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    let recurrence: Recurrence = recurrence.parse().map_err(ServerFnError::new)?;
+    if due_date.is_none() {
+        return Err(ServerFnError::new("A recurring task needs a due date"));
+    }
+
+    Ok(Some(recurrence.to_string()))
+}
+
+#[server]
+pub async fn create_task(
+    title: String,
+    due_date: Option<NaiveDate>,
+    due_time: Option<NaiveTime>,
+    recurrence: Option<String>,
+    priority: Priority,
+    parent_id: Option<Uuid>,
+    list_id: Uuid,
+) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    List::authorize(list_id, user.id, Role::Editor, &pool).await?;
+
+    if let Some(parent_id) = parent_id {
+        let parent = authorize_task(parent_id, &user, Role::Editor, &pool).await?;
+        if parent.list_id != list_id {
+            return Err(ServerFnError::new(
+                "A subtask must be in the list of its parent",
+            ));
+        }
+    }
+
+    let (due_date, due_at) = resolve_due(due_date, due_time, &user)?;
+    let recurrence = resolve_recurrence(recurrence, due_date)?;
+    let task = Task::new(
+        title, due_date, due_at, recurrence, priority, parent_id, list_id, user.id,
+    );
+
+    task.create_task(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn get_task_tree(list_id: Uuid) -> Result<Vec<TaskNode>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    List::authorize(list_id, user.id, Role::Viewer, &pool).await?;
+
+    let tree = Task::get_task_tree(list_id, &pool).await?;
+    Ok(tree)
+}
+
+/// Tasks of every list of the user in one of the built-in views, with days
+/// counted in the time zone of the user.
+#[server]
+pub async fn get_smart_view(view: SmartView) -> Result<Vec<Task>, ServerFnError> {
+    use chrono::Days;
+
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let tz = user.tz();
+    let today = time_zone::today(tz);
+    let day = |days: u64| {
+        let date = today + Days::new(days);
+        (date, time_zone::start_of_day(date, tz))
+    };
+
+    let tasks = match view {
+        SmartView::Today => {
+            let (start_date, start_at) = day(0);
+            let (end_date, end_at) = day(1);
+            Task::get_user_tasks_due_between(user.id, start_date, start_at, end_date, end_at, &pool)
+                .await?
+        }
+        SmartView::Upcoming => {
+            let (start_date, start_at) = day(1);
+            let (end_date, end_at) = day(1 + SmartView::UPCOMING_DAYS);
+            Task::get_user_tasks_due_between(user.id, start_date, start_at, end_date, end_at, &pool)
+                .await?
+        }
+        SmartView::Overdue => {
+            Task::get_user_overdue_tasks(user.id, today, Utc::now(), &pool).await?
+        }
+        SmartView::Completed => {
+            let since = time_zone::start_of_day(today - Days::new(SmartView::COMPLETED_DAYS), tz);
+            Task::get_user_completed_tasks(user.id, since, &pool).await?
+        }
+    };
+
+    Ok(tasks)
+}
+
+#[server]
+pub async fn get_tasks(list_id: Uuid) -> Result<Vec<Task>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    List::authorize(list_id, user.id, Role::Viewer, &pool).await?;
+
+    let tasks = Task::get_list_tasks(list_id, &pool).await?;
+    Ok(tasks)
+}
+
+/// Completes the task, then each parent set to complete itself once its last
+/// open subtask is done.
+#[cfg(feature = "server")]
+async fn complete_with_parents(
+    mut task: Task,
+    user: &User,
+    pool: &SqlitePool,
+) -> Result<(), Error> {
+    loop {
+        let next = match task.series_id {
+            Some(series_id) if task.completed_at.is_none() => {
+                let occurrences = Task::count_series_tasks(series_id, pool).await?;
+                task.next_occurrence(occurrences, user.tz(), user.id)
+            }
+            _ => None,
+        };
+
+        task.complete_task(user.id, next.as_ref(), pool).await?;
+
+        let Some(parent_id) = task.parent_id else {
+            return Ok(());
+        };
+        let Some(parent) = Task::get_task(parent_id, pool).await? else {
+            return Ok(());
+        };
+        if !parent.auto_complete
+            || parent.completed_at.is_some()
+            || Task::count_open_subtasks(parent.id, pool).await? > 0
+        {
+            return Ok(());
+        }
+
+        task = parent;
+    }
+}
+
+#[server]
+pub async fn complete_task(task: Task) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let task = authorize_task(task.id, &user, Role::Editor, &pool).await?;
+    complete_with_parents(task, &user, &pool).await?;
+    Ok(())
+}
+
+/// Moves the task under another task of the same list, or back to the top
+/// level when `parent_id` is `None`.
+#[server]
+pub async fn move_task(task_id: Uuid, parent_id: Option<Uuid>) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let task = authorize_task(task_id, &user, Role::Editor, &pool).await?;
+
+    if let Some(parent_id) = parent_id {
+        let parent = authorize_task(parent_id, &user, Role::Editor, &pool).await?;
+        if parent.list_id != task.list_id {
+            return Err(ServerFnError::new(
+                "A subtask must be in the list of its parent",
+            ));
+        }
+
+        let ancestor_ids = Task::get_ancestor_ids(parent_id, &pool).await?;
+        if ancestor_ids.contains(&task.id) {
+            return Err(ServerFnError::new(
+                "A task cannot be moved under itself or one of its subtasks",
+            ));
+        }
+    }
+
+    let task = Task { parent_id, ..task };
+    task.move_task(&pool).await?;
+    Ok(())
+}
+
+/// Moves the task to the place of `target_id`, a task with the same parent,
+/// as when dropping one onto the other.
+#[server]
+pub async fn reorder_task(task_id: Uuid, target_id: Uuid) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let task = authorize_task(task_id, &user, Role::Editor, &pool).await?;
+    let target = authorize_task(target_id, &user, Role::Editor, &pool).await?;
+
+    if task.id == target.id {
+        return Ok(());
+    }
+    if task.list_id != target.list_id || task.parent_id != target.parent_id {
+        return Err(ServerFnError::new(
+            "A task can only be reordered among the tasks of the same parent",
+        ));
+    }
+
+    task.reorder_task(&target, &pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn set_auto_complete(task_id: Uuid, auto_complete: bool) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let task = authorize_task(task_id, &user, Role::Editor, &pool).await?;
+
+    let task = Task {
+        auto_complete,
+        ..task
+    };
+    task.set_auto_complete(&pool).await?;
+    Ok(())
+}
+
+/// Every occurrence of a recurring task, oldest first, or just the task when
+/// it never repeated.
+#[server]
+pub async fn get_task_history(task_id: Uuid) -> Result<Vec<Task>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let task = authorize_task(task_id, &user, Role::Viewer, &pool).await?;
+
+    let tasks = match task.series_id {
+        Some(series_id) => Task::get_series_tasks(series_id, &pool).await?,
+        None => vec![task],
+    };
+
+    Ok(tasks)
+}
+
+#[server]
+pub async fn uncomplete_task(task: Task) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let task = authorize_task(task.id, &user, Role::Editor, &pool).await?;
+    task.uncomplete_task(&pool).await?;
+    Ok(())
+}
+
+/// Renames the task, moves its due date and changes how it repeats and its
+/// priority, with the due time read in the time zone of the user.
+#[server]
+pub async fn update_task(
+    task_id: Uuid,
+    title: String,
+    due_date: Option<NaiveDate>,
+    due_time: Option<NaiveTime>,
+    recurrence: Option<String>,
+    priority: Priority,
+) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let title = title.trim().to_owned();
+    if title.is_empty() {
+        return Err(ServerFnError::new("Title cannot be empty"));
+    }
+
+    let task = authorize_task(task_id, &user, Role::Editor, &pool).await?;
+    let (due_date, due_at) = resolve_due(due_date, due_time, &user)?;
+    let recurrence = resolve_recurrence(recurrence, due_date)?;
+    let task = Task {
+        title,
+        due_date,
+        due_at,
+        // Occurrences stay linked when the task stops repeating, so its
+        // history isn't lost.
+        series_id: task.series_id.or(recurrence.as_ref().map(|_| task.id)),
+        recurrence,
+        priority,
+        ..task
+    };
+
+    task.update_task(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn delete_task(task: Task) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let task = authorize_task(task.id, &user, Role::Editor, &pool).await?;
+    task.delete_task(&pool).await?;
+    Ok(())
+}
+
+#[cfg(feature = "server")]
+const LABEL_NAME_MAX_LENGTH: usize = 32;
+
+/// Trims the name of a label and checks it along with its `#rrggbb` color.
+#[cfg(feature = "server")]
+fn resolve_label(name: String, color: String) -> Result<(String, String), ServerFnError> {
+    let name = name.trim().to_owned();
+    if name.is_empty() {
+        return Err(ServerFnError::new("Label name cannot be empty"));
+    }
+    if name.chars().count() > LABEL_NAME_MAX_LENGTH {
+        return Err(ServerFnError::new(format!(
+            "Label name must be at most {LABEL_NAME_MAX_LENGTH} characters"
+        )));
+    }
+
+    let color = color.trim().to_lowercase();
+    let valid_color = color.len() == 7
+        && color
+            .strip_prefix('#')
+            .is_some_and(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()));
+    if !valid_color {
+        return Err(ServerFnError::new("Label color must look like #rrggbb"));
+    }
+
+    Ok((name, color))
+}
+
+#[server]
+pub async fn get_labels() -> Result<Vec<Label>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let labels = Label::get_user_labels(user.id, &pool).await?;
+    Ok(labels)
+}
+
+#[server]
+pub async fn get_label(label_id: Uuid) -> Result<Label, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let label = Label::get_label(label_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    Ok(label)
+}
+
+#[server]
+pub async fn create_label(name: String, color: String) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let (name, color) = resolve_label(name, color)?;
+    let label = Label::new(name, color, user.id);
+
+    label.create_label(&pool).await?;
+    Ok(())
+}
+
+/// Renames and recolors the label.
+#[server]
+pub async fn update_label(
+    label_id: Uuid,
+    name: String,
+    color: String,
+) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let label = Label::get_label(label_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    let (name, color) = resolve_label(name, color)?;
+    let label = Label {
+        name,
+        color,
+        ..label
+    };
+
+    label.update_label(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn delete_label(label_id: Uuid) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let label = Label::get_label(label_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    label.delete_label(&pool).await?;
+    Ok(())
+}
+
+/// Labels of the user on the tasks of the list.
+#[server]
+pub async fn get_task_labels(list_id: Uuid) -> Result<Vec<TaskLabel>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    List::authorize(list_id, user.id, Role::Viewer, &pool).await?;
+
+    let task_labels = TaskLabel::get_list_task_labels(list_id, user.id, &pool).await?;
+    Ok(task_labels)
+}
+
+/// Puts one of the user's labels on a task. Labels are private, so viewers
+/// of a list may label its tasks too.
+#[server]
+pub async fn attach_label(task_id: Uuid, label_id: Uuid) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    authorize_task(task_id, &user, Role::Viewer, &pool).await?;
+    Label::get_label(label_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+
+    let task_label = TaskLabel { task_id, label_id };
+    task_label.attach_label(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn detach_label(task_id: Uuid, label_id: Uuid) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    authorize_task(task_id, &user, Role::Viewer, &pool).await?;
+    Label::get_label(label_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+
+    let task_label = TaskLabel { task_id, label_id };
+    task_label.detach_label(&pool).await?;
+    Ok(())
+}
+
+/// Tasks carrying the label across every list the user can access.
+#[server]
+pub async fn get_label_tasks(label_id: Uuid) -> Result<Vec<Task>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    Label::get_label(label_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+
+    let tasks = Task::get_label_tasks(label_id, user.id, &pool).await?;
+    Ok(tasks)
+}
+
+#[cfg(feature = "server")]
+const FILTER_NAME_MAX_LENGTH: usize = 64;
+
+/// Trims the name and query of a saved filter, and checks that the query
+/// parses.
+#[cfg(feature = "server")]
+fn resolve_saved_filter(name: String, query: String) -> Result<(String, String), ServerFnError> {
+    let name = name.trim().to_owned();
+    if name.is_empty() {
+        return Err(ServerFnError::new("Filter name cannot be empty"));
+    }
+    if name.chars().count() > FILTER_NAME_MAX_LENGTH {
+        return Err(ServerFnError::new(format!(
+            "Filter name must be at most {FILTER_NAME_MAX_LENGTH} characters"
+        )));
+    }
+
+    let query = query.trim().to_owned();
+    query.parse::<Filter>().map_err(ServerFnError::new)?;
+
+    Ok((name, query))
+}
+
+#[server]
+pub async fn get_saved_filters() -> Result<Vec<SavedFilter>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let saved_filters = SavedFilter::get_user_saved_filters(user.id, &pool).await?;
+    Ok(saved_filters)
+}
+
+#[server]
+pub async fn get_saved_filter(saved_filter_id: Uuid) -> Result<SavedFilter, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let saved_filter = SavedFilter::get_saved_filter(saved_filter_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    Ok(saved_filter)
+}
+
+#[server]
+pub async fn create_saved_filter(name: String, query: String) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let (name, query) = resolve_saved_filter(name, query)?;
+    let saved_filter = SavedFilter::new(name, query, user.id);
+
+    saved_filter.create_saved_filter(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn update_saved_filter(
+    saved_filter_id: Uuid,
+    name: String,
+    query: String,
+) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let saved_filter = SavedFilter::get_saved_filter(saved_filter_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    let (name, query) = resolve_saved_filter(name, query)?;
+    let saved_filter = SavedFilter {
+        name,
+        query,
+        ..saved_filter
+    };
+
+    saved_filter.update_saved_filter(&pool).await?;
+    Ok(())
+}
+
+#[server]
+pub async fn delete_saved_filter(saved_filter_id: Uuid) -> Result<(), ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!().await?;
+
+    let saved_filter = SavedFilter::get_saved_filter(saved_filter_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    saved_filter.delete_saved_filter(&pool).await?;
+    Ok(())
+}
+
+/// Tasks of every list of the user matching a saved filter, with days
+/// counted in the time zone of the user.
+#[server]
+pub async fn get_saved_filter_tasks(saved_filter_id: Uuid) -> Result<Vec<Task>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let saved_filter = SavedFilter::get_saved_filter(saved_filter_id, user.id, &pool)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    let filter: Filter = saved_filter.query.parse().map_err(ServerFnError::new)?;
+    let today = time_zone::today(user.tz());
+
+    let tasks = Task::get_filtered_tasks(&filter, user.id, today, Utc::now(), &pool).await?;
+    Ok(tasks)
+}
+
+#[cfg(feature = "server")]
+const SEARCH_LIMIT: u32 = 50;
+
+/// Tasks and lists of the user whose title matches every word of `text`,
+/// best matches first.
+#[server]
+pub async fn search(text: String) -> Result<Vec<SearchResult>, ServerFnError> {
+    let pool = pool!().await?;
+    let user = user!(read).await?;
+
+    let results = SearchResult::search(&text, user.id, SEARCH_LIMIT, &pool).await?;
+    Ok(results)
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use crate::repository::{test_pool, test_user};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn authorize_task_forbids_non_members() {
+        let pool = test_pool().await;
+        let owner = test_user("owner", &pool).await;
+        let stranger = test_user("stranger", &pool).await;
+        let list = List::new("Groceries".to_owned(), owner.id);
+        list.create_list(&pool).await.unwrap();
+        let task = Task::new(
+            "Milk".to_owned(),
+            None,
+            None,
+            None,
+            Priority::None,
+            None,
+            list.id,
+            owner.id,
+        );
+        task.create_task(&pool).await.unwrap();
+
+        // `get_tasks` reads with the viewer role, `update_task` and
+        // `delete_task` need the editor role.
+        for required in [Role::Viewer, Role::Editor] {
+            let result = authorize_task(task.id, &stranger, required, &pool).await;
+            assert!(matches!(result, Err(Error::Forbidden)), "{required:?}");
+        }
+        let result = authorize_task(Uuid::new_v4(), &owner, Role::Viewer, &pool).await;
+        assert!(matches!(result, Err(Error::Forbidden)));
+        let result = authorize_task(task.id, &owner, Role::Editor, &pool).await;
+        assert_eq!(result.unwrap().id, task.id);
+    }
+
+    #[tokio::test]
+    async fn oidc_links_the_logged_in_user() {
+        let pool = test_pool().await;
+        let provider = oidc::mock::MockProvider::start().await;
+        let alice = test_user("alice", &pool).await;
+
+        let claims = provider.login("alice-at-provider").await;
+        let user = identity_user(&provider.config, claims, Some(alice.clone()), &pool).await;
+        assert_eq!(user.unwrap().id, alice.id);
+
+        // Later logins find the linked user without a session.
+        let claims = provider.login("alice-at-provider").await;
+        let user = identity_user(&provider.config, claims, None, &pool).await;
+        assert_eq!(user.unwrap().id, alice.id);
+    }
+
+    #[tokio::test]
+    async fn oidc_provisions_unknown_users_only_when_enabled() {
+        let pool = test_pool().await;
+        let mut provider = oidc::mock::MockProvider::start().await;
+
+        let claims = provider.login("bob").await;
+        let result = identity_user(&provider.config, claims, None, &pool).await;
+        assert!(matches!(result, Err(Error::UserNotFound)));
+
+        provider.config.auto_provision = true;
+        let claims = provider.login("bob").await;
+        let bob = identity_user(&provider.config, claims, None, &pool)
+            .await
+            .unwrap();
+        assert_eq!(bob.username, "bob");
+
+        let claims = provider.login("bob").await;
+        let user = identity_user(&provider.config, claims, None, &pool).await;
+        assert_eq!(user.unwrap().id, bob.id);
+    }
+}
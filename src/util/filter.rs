@@ -0,0 +1,619 @@
+//! The query language of saved filters, such as
+//! `due:<7d label:work !completed list:"Sprint 12"`.
+//!
+//! A filter is a list of terms separated by spaces, all of which a task must
+//! match. A term prefixed with `!` matches the tasks the term alone doesn't.
+//!
+//! - `due:` compares the due day with `today`, `tomorrow`, a date such as
+//!   `2025-04-01` or a number of days or weeks from today such as `7d` or
+//!   `2w`, optionally after `<`, `<=`, `>` or `>=`. `due:none` matches tasks
+//!   without a due date.
+//! - `label:`, `list:` match the name of a label of the user or the title of
+//!   a list, ignoring case.
+//! - `priority:` compares with `none`, `low`, `medium` or `high` the same way
+//!   as `due:`.
+//! - `completed` and `overdue` match completed and overdue tasks.
+//! - Any other word matches tasks with that word in their title.
+//!
+//! Values with spaces, or words that would otherwise be read as keywords, go
+//! in double quotes, with `\"` and `\\` for quotes and backslashes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+
+use crate::model::Priority;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "=",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Greater => ">",
+        }
+    }
+
+    /// Splits a leading comparison operator off `value`, `=` when there is
+    /// none.
+    fn split(value: &str) -> (Comparison, &str) {
+        [
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+            ("=", Comparison::Equal),
+        ]
+        .into_iter()
+        .find_map(|(operator, comparison)| {
+            value
+                .strip_prefix(operator)
+                .map(|value| (comparison, value))
+        })
+        .unwrap_or((Comparison::Equal, value))
+    }
+}
+
+/// A day to compare due dates with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Day {
+    /// Days from today, negative ones in the past.
+    Relative(i64),
+    Date(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// The title contains the text, ignoring case.
+    Title(String),
+    Completed,
+    Overdue,
+    NoDueDate,
+    Due(Comparison, Day),
+    Label(String),
+    List(String),
+    Priority(Comparison, Priority),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub negated: bool,
+    pub condition: Condition,
+}
+
+/// Terms a task must all match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    pub terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ErrorKind {
+    #[error("The filter is empty")]
+    Empty,
+
+    #[error("Expected a term after `!`")]
+    MissingTerm,
+
+    #[error("Missing closing quote")]
+    UnterminatedQuote,
+
+    #[error("Expected a key before `:`")]
+    MissingKey,
+
+    #[error("Unknown key `{0}`, expected due, label, list or priority")]
+    UnknownKey(String),
+
+    #[error("`{0}:` needs a value")]
+    MissingValue(String),
+
+    #[error(
+        "Invalid due value `{0}`, expected today, tomorrow, none, a date such as 2025-04-01 \
+        or a number of days or weeks such as 7d or 2w"
+    )]
+    InvalidDue(String),
+
+    #[error("Invalid priority `{0}`, expected none, low, medium or high")]
+    InvalidPriority(String),
+}
+
+/// Error of a filter, along with the column it starts at, counting from 1.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{kind} (column {column})")]
+pub struct ParseError {
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+/// Reads the terms of a filter one by one.
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    query: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(query: &'a str) -> Self {
+        Parser {
+            chars: query.char_indices().peekable(),
+            query,
+        }
+    }
+
+    /// Column of the character at byte `index`.
+    fn column(&self, index: usize) -> usize {
+        self.query[..index].chars().count() + 1
+    }
+
+    fn error(&self, index: usize, kind: ErrorKind) -> ParseError {
+        ParseError {
+            column: self.column(index),
+            kind,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    /// Byte index of the next character, or the end of the query.
+    fn index(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map(|&(index, _)| index)
+            .unwrap_or(self.query.len())
+    }
+
+    /// Reads a quoted string, the opening quote being next.
+    fn quoted(&mut self) -> Result<String, ParseError> {
+        let (start, _) = self.chars.next().unwrap();
+        let mut value = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(value),
+                Some((_, '\\')) => match self.chars.next() {
+                    Some((_, c)) => value.push(c),
+                    None => break,
+                },
+                Some((_, c)) => value.push(c),
+                None => break,
+            }
+        }
+
+        Err(self.error(start, ErrorKind::UnterminatedQuote))
+    }
+
+    /// Reads characters up to the next space, or `stop` if any.
+    fn word(&mut self, stop: impl Fn(char) -> bool) -> String {
+        let mut word = String::new();
+        while let Some((_, c)) = self.chars.next_if(|&(_, c)| !c.is_whitespace() && !stop(c)) {
+            word.push(c);
+        }
+        word
+    }
+
+    /// Reads the next term, `None` at the end of the query.
+    fn term(&mut self) -> Result<Option<Term>, ParseError> {
+        self.skip_whitespace();
+
+        let start = self.index();
+        if start == self.query.len() {
+            return Ok(None);
+        }
+
+        let negated = self.chars.next_if(|&(_, c)| c == '!').is_some();
+
+        let condition = match self.chars.peek() {
+            Some((_, '"')) => Condition::Title(self.quoted()?),
+            Some((_, c)) if !c.is_whitespace() => {
+                let word = self.word(|c| c == ':' || c == '"');
+
+                if self.chars.next_if(|&(_, c)| c == ':').is_some() {
+                    let value_start = self.index();
+                    let value = match self.chars.peek() {
+                        Some((_, '"')) => self.quoted()?,
+                        _ => self.word(|_| false),
+                    };
+                    self.condition(&word, value, start, value_start)?
+                } else {
+                    match word.as_str() {
+                        "completed" => Condition::Completed,
+                        "overdue" => Condition::Overdue,
+                        _ => Condition::Title(word),
+                    }
+                }
+            }
+            _ => return Err(self.error(start, ErrorKind::MissingTerm)),
+        };
+
+        Ok(Some(Term { negated, condition }))
+    }
+
+    /// Condition of a `key:value` term.
+    fn condition(
+        &self,
+        key: &str,
+        value: String,
+        key_start: usize,
+        value_start: usize,
+    ) -> Result<Condition, ParseError> {
+        if key.is_empty() {
+            return Err(self.error(key_start, ErrorKind::MissingKey));
+        }
+
+        let key = key.to_lowercase();
+        if !["due", "label", "list", "priority"].contains(&key.as_str()) {
+            return Err(self.error(key_start, ErrorKind::UnknownKey(key)));
+        }
+        if value.is_empty() {
+            return Err(self.error(value_start, ErrorKind::MissingValue(key)));
+        }
+
+        let condition = match key.as_str() {
+            "due" => parse_due(&value)
+                .ok_or_else(|| self.error(value_start, ErrorKind::InvalidDue(value)))?,
+            "label" => Condition::Label(value),
+            "list" => Condition::List(value),
+            _ => {
+                let (comparison, priority) = Comparison::split(&value);
+                let priority = priority
+                    .to_lowercase()
+                    .parse()
+                    .map_err(|_| self.error(value_start, ErrorKind::InvalidPriority(value)))?;
+                Condition::Priority(comparison, priority)
+            }
+        };
+
+        Ok(condition)
+    }
+}
+
+/// Relative days go at most about a hundred years either way, keeping the
+/// compared dates within four digit years.
+const MAX_RELATIVE_DAYS: i64 = 36_500;
+
+fn parse_due(value: &str) -> Option<Condition> {
+    let value = value.to_lowercase();
+    if value == "none" {
+        return Some(Condition::NoDueDate);
+    }
+
+    let (comparison, day) = Comparison::split(&value);
+    let day = match day {
+        "today" => Day::Relative(0),
+        "tomorrow" => Day::Relative(1),
+        day => match day.strip_suffix('d') {
+            Some(days) => Day::Relative(days.parse().ok()?),
+            None => match day.strip_suffix('w') {
+                Some(weeks) => Day::Relative(weeks.parse::<i64>().ok()?.checked_mul(7)?),
+                None => Day::Date(NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?),
+            },
+        },
+    };
+    if let Day::Relative(days) = day {
+        if days.abs() > MAX_RELATIVE_DAYS {
+            return None;
+        }
+    }
+
+    Some(Condition::Due(comparison, day))
+}
+
+impl FromStr for Filter {
+    type Err = ParseError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(query);
+        let mut terms = Vec::new();
+
+        while let Some(term) = parser.term()? {
+            terms.push(term);
+        }
+
+        if terms.is_empty() {
+            return Err(ParseError {
+                column: 1,
+                kind: ErrorKind::Empty,
+            });
+        }
+
+        Ok(Filter { terms })
+    }
+}
+
+/// Quotes `value` when it can't be written as is.
+fn quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\\' | ':' | '!'));
+
+    if plain {
+        value.to_owned()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Formats the filter back into the query language.
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self
+            .terms
+            .iter()
+            .map(|term| {
+                let condition = match &term.condition {
+                    Condition::Title(text) if matches!(text.as_str(), "completed" | "overdue") => {
+                        format!("\"{text}\"")
+                    }
+                    Condition::Title(text) => quote(text),
+                    Condition::Completed => "completed".to_owned(),
+                    Condition::Overdue => "overdue".to_owned(),
+                    Condition::NoDueDate => "due:none".to_owned(),
+                    Condition::Due(comparison, day) => {
+                        let operator = match comparison {
+                            Comparison::Equal => "",
+                            comparison => comparison.as_sql(),
+                        };
+                        let day = match day {
+                            Day::Relative(days) => format!("{days}d"),
+                            Day::Date(date) => date.format("%Y-%m-%d").to_string(),
+                        };
+                        format!("due:{operator}{day}")
+                    }
+                    Condition::Label(name) => format!("label:{}", quote(name)),
+                    Condition::List(title) => format!("list:{}", quote(title)),
+                    Condition::Priority(comparison, priority) => {
+                        let operator = match comparison {
+                            Comparison::Equal => "",
+                            comparison => comparison.as_sql(),
+                        };
+                        format!("priority:{operator}{}", priority.as_str())
+                    }
+                };
+
+                if term.negated {
+                    format!("!{condition}")
+                } else {
+                    condition
+                }
+            })
+            .collect();
+
+        write!(f, "{}", terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(negated: bool, condition: Condition) -> Term {
+        Term { negated, condition }
+    }
+
+    fn error(query: &str) -> ParseError {
+        query.parse::<Filter>().unwrap_err()
+    }
+
+    #[test]
+    fn parses_the_example() {
+        let filter: Filter = r#"due:<7d label:work !completed list:"Sprint 12""#.parse().unwrap();
+
+        assert_eq!(
+            filter.terms,
+            vec![
+                term(false, Condition::Due(Comparison::Less, Day::Relative(7))),
+                term(false, Condition::Label("work".to_owned())),
+                term(true, Condition::Completed),
+                term(false, Condition::List("Sprint 12".to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_due_values() {
+        let due = |query: &str| query.parse::<Filter>().unwrap().terms.remove(0).condition;
+
+        assert_eq!(
+            due("due:today"),
+            Condition::Due(Comparison::Equal, Day::Relative(0))
+        );
+        assert_eq!(
+            due("DUE:Tomorrow"),
+            Condition::Due(Comparison::Equal, Day::Relative(1))
+        );
+        assert_eq!(
+            due("due:>=2w"),
+            Condition::Due(Comparison::GreaterOrEqual, Day::Relative(14))
+        );
+        assert_eq!(
+            due("due:>-3d"),
+            Condition::Due(Comparison::Greater, Day::Relative(-3))
+        );
+        assert_eq!(
+            due("due:<=2025-04-01"),
+            Condition::Due(
+                Comparison::LessOrEqual,
+                Day::Date(NaiveDate::from_ymd_opt(2025, 4, 1).unwrap())
+            )
+        );
+        assert_eq!(due("due:none"), Condition::NoDueDate);
+        assert_eq!(
+            due("priority:>low"),
+            Condition::Priority(Comparison::Greater, Priority::Low)
+        );
+        assert_eq!(due("overdue"), Condition::Overdue);
+        assert_eq!(due("milk"), Condition::Title("milk".to_owned()));
+    }
+
+    #[test]
+    fn reports_errors_with_their_column() {
+        assert_eq!(
+            error("   "),
+            ParseError {
+                column: 1,
+                kind: ErrorKind::Empty,
+            }
+        );
+        assert_eq!(
+            error("milk !"),
+            ParseError {
+                column: 6,
+                kind: ErrorKind::MissingTerm,
+            }
+        );
+        assert_eq!(
+            error(r#"milk "eggs"#),
+            ParseError {
+                column: 6,
+                kind: ErrorKind::UnterminatedQuote,
+            }
+        );
+        assert_eq!(
+            error(r#"list:"Sprint 12"#),
+            ParseError {
+                column: 6,
+                kind: ErrorKind::UnterminatedQuote,
+            }
+        );
+        assert_eq!(
+            error("milk :work"),
+            ParseError {
+                column: 6,
+                kind: ErrorKind::MissingKey,
+            }
+        );
+        assert_eq!(
+            error("milk Tag:work"),
+            ParseError {
+                column: 6,
+                kind: ErrorKind::UnknownKey("tag".to_owned()),
+            }
+        );
+        assert_eq!(
+            error("milk label: eggs"),
+            ParseError {
+                column: 12,
+                kind: ErrorKind::MissingValue("label".to_owned()),
+            }
+        );
+        assert_eq!(
+            error("milk due:soon"),
+            ParseError {
+                column: 10,
+                kind: ErrorKind::InvalidDue("soon".to_owned()),
+            }
+        );
+        assert_eq!(
+            error("milk priority:>urgent"),
+            ParseError {
+                column: 15,
+                kind: ErrorKind::InvalidPriority(">urgent".to_owned()),
+            }
+        );
+    }
+
+    #[test]
+    fn bounds_relative_days() {
+        assert!("due:<36500d".parse::<Filter>().is_ok());
+        assert!("due:>-5214w".parse::<Filter>().is_ok());
+        assert_eq!(
+            error("due:<36501d").kind,
+            ErrorKind::InvalidDue("<36501d".to_owned())
+        );
+        assert_eq!(
+            error("due:-5215w").kind,
+            ErrorKind::InvalidDue("-5215w".to_owned())
+        );
+        assert_eq!(
+            error("due:9223372036854775807d").kind,
+            ErrorKind::InvalidDue("9223372036854775807d".to_owned())
+        );
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        assert_eq!(error("café due:x").column, 10);
+    }
+
+    #[test]
+    fn formats_errors_with_their_column() {
+        assert_eq!(error("due:").to_string(), "`due:` needs a value (column 5)");
+    }
+
+    #[test]
+    fn unescapes_quoted_values() {
+        let filter: Filter = r#""say \"hi\" \\ now" label:"a\"b""#.parse().unwrap();
+
+        assert_eq!(
+            filter.terms,
+            vec![
+                term(false, Condition::Title(r#"say "hi" \ now"#.to_owned())),
+                term(false, Condition::Label(r#"a"b"#.to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn quotes_keywords_in_titles() {
+        let filter: Filter = r#""completed" !"overdue" "due:today""#.parse().unwrap();
+
+        assert_eq!(
+            filter.terms,
+            vec![
+                term(false, Condition::Title("completed".to_owned())),
+                term(true, Condition::Title("overdue".to_owned())),
+                term(false, Condition::Title("due:today".to_owned())),
+            ]
+        );
+        assert_eq!(filter.to_string(), r#""completed" !"overdue" "due:today""#);
+    }
+
+    #[test]
+    fn formats_canonical_queries_unchanged() {
+        for query in [
+            r#"due:<7d label:work !completed list:"Sprint 12""#,
+            "due:>=2025-04-01 !due:none priority:>medium overdue",
+            r#"milk "two words" "a \"quoted\" \\ word" label:"!x""#,
+            "due:-3d priority:low",
+        ] {
+            assert_eq!(query.parse::<Filter>().unwrap().to_string(), query);
+        }
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        let filter = Filter {
+            terms: vec![
+                term(false, Condition::Title(String::new())),
+                term(true, Condition::Title("Completed".to_owned())),
+                term(false, Condition::Title("a:b".to_owned())),
+                term(false, Condition::Title("!important".to_owned())),
+                term(false, Condition::Label("back\\slash".to_owned())),
+                term(true, Condition::List("my list".to_owned())),
+                term(
+                    false,
+                    Condition::Due(Comparison::LessOrEqual, Day::Relative(14)),
+                ),
+                term(
+                    false,
+                    Condition::Priority(Comparison::Equal, Priority::None),
+                ),
+            ],
+        };
+
+        assert_eq!(filter.to_string().parse::<Filter>().unwrap(), filter);
+    }
+}
@@ -1,2 +1,18 @@
+pub mod filter;
+#[cfg(feature = "server")]
+pub mod mail;
+#[cfg(feature = "server")]
+pub mod oidc;
 #[cfg(feature = "server")]
 pub mod password_hash;
+#[cfg(feature = "server")]
+pub mod rate_limit;
+pub mod recurrence;
+#[cfg(feature = "server")]
+pub mod signature;
+pub mod time_zone;
+#[cfg(feature = "server")]
+pub mod token;
+#[cfg(feature = "server")]
+pub mod totp;
+pub mod validation;
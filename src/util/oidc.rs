@@ -0,0 +1,506 @@
+//! OpenID Connect authorization code flow with PKCE, configured through the
+//! `OIDC_ISSUER`, `OIDC_CLIENT_ID`, `OIDC_CLIENT_SECRET` (optional),
+//! `OIDC_REDIRECT_URL` and `OIDC_AUTO_PROVISION` env vars.
+//!
+//! The ID token comes straight from the token endpoint over TLS, which the
+//! spec allows in place of checking its signature; its issuer, audience,
+//! expiry and nonce are still validated.
+
+use std::env;
+use std::sync::LazyLock;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_url: String,
+    /// Whether to create a user on the first login of an unknown subject.
+    pub auto_provision: bool,
+}
+
+/// `None` when no issuer is configured, which disables OIDC login. Forced at
+/// startup so that a partial configuration fails right away.
+pub static CONFIG: LazyLock<Option<Config>> = LazyLock::new(|| {
+    let issuer = env::var("OIDC_ISSUER").ok()?;
+    assert!(is_https(&issuer), "OIDC_ISSUER must be an https URL");
+
+    Some(Config {
+        issuer: issuer.trim_end_matches('/').to_owned(),
+        client_id: env::var("OIDC_CLIENT_ID").expect("OIDC_CLIENT_ID is required"),
+        client_secret: env::var("OIDC_CLIENT_SECRET").ok(),
+        redirect_url: env::var("OIDC_REDIRECT_URL").expect("OIDC_REDIRECT_URL is required"),
+        auto_provision: env::var("OIDC_AUTO_PROVISION").is_ok_and(|value| value == "true"),
+    })
+});
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Http error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Invalid provider response: {0}")]
+    InvalidResponse(&'static str),
+}
+
+/// State kept in the session between the redirect and the callback.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pending {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Claims {
+    iss: String,
+    aud: Audience,
+    exp: i64,
+    nonce: Option<String>,
+    pub sub: String,
+    pub preferred_username: Option<String>,
+    pub email: Option<String>,
+}
+
+/// Whether the URL uses TLS, which the unsigned ID token relies on. Tests run
+/// a plain http provider on localhost.
+fn is_https(url: &str) -> bool {
+    cfg!(test) || url.starts_with("https://")
+}
+
+fn random() -> String {
+    let mut bytes = [0; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+async fn discover(config: &Config) -> Result<Discovery, Error> {
+    let url = format!("{}/.well-known/openid-configuration", config.issuer);
+    let discovery: Discovery = reqwest::get(url).await?.error_for_status()?.json().await?;
+
+    if discovery.issuer.trim_end_matches('/') != config.issuer {
+        return Err(Error::InvalidResponse("issuer mismatch"));
+    }
+    if !is_https(&discovery.authorization_endpoint) || !is_https(&discovery.token_endpoint) {
+        return Err(Error::InvalidResponse("endpoint without https"));
+    }
+
+    Ok(discovery)
+}
+
+/// Builds the URL to send the user to, along with the state to keep until
+/// the callback.
+pub async fn authorization_url(config: &Config) -> Result<(String, Pending), Error> {
+    let discovery = discover(config).await?;
+
+    let pending = Pending {
+        state: random(),
+        nonce: random(),
+        code_verifier: random(),
+    };
+    let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(pending.code_verifier.as_bytes()));
+
+    let url = reqwest::Url::parse_with_params(
+        &discovery.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", config.client_id.as_str()),
+            ("redirect_uri", config.redirect_url.as_str()),
+            ("scope", "openid profile email"),
+            ("state", pending.state.as_str()),
+            ("nonce", pending.nonce.as_str()),
+            ("code_challenge", code_challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|_| Error::InvalidResponse("invalid authorization endpoint"))?;
+
+    Ok((url.into(), pending))
+}
+
+/// Exchanges the authorization code and returns the validated ID token
+/// claims.
+pub async fn exchange_code(
+    config: &Config,
+    code: &str,
+    pending: &Pending,
+) -> Result<Claims, Error> {
+    let discovery = discover(config).await?;
+
+    let mut params = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", config.redirect_url.as_str()),
+        ("client_id", config.client_id.as_str()),
+        ("code_verifier", pending.code_verifier.as_str()),
+    ];
+    if let Some(client_secret) = &config.client_secret {
+        params.push(("client_secret", client_secret.as_str()));
+    }
+
+    let response: TokenResponse = reqwest::Client::new()
+        .post(&discovery.token_endpoint)
+        .form(&params)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    validate_id_token(
+        &response.id_token,
+        config,
+        &pending.nonce,
+        chrono::Utc::now().timestamp(),
+    )
+}
+
+/// Decodes the ID token and checks its issuer, audience, expiry against the
+/// `now` unix time, and nonce.
+fn validate_id_token(
+    id_token: &str,
+    config: &Config,
+    nonce: &str,
+    now: i64,
+) -> Result<Claims, Error> {
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .ok_or(Error::InvalidResponse("malformed ID token"))?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| Error::InvalidResponse("malformed ID token"))?;
+    let claims: Claims = serde_json::from_slice(&payload)
+        .map_err(|_| Error::InvalidResponse("malformed ID token"))?;
+
+    if claims.iss.trim_end_matches('/') != config.issuer {
+        return Err(Error::InvalidResponse("issuer mismatch"));
+    }
+
+    let audience_matches = match &claims.aud {
+        Audience::One(audience) => audience == &config.client_id,
+        Audience::Many(audiences) => audiences.contains(&config.client_id),
+    };
+    if !audience_matches {
+        return Err(Error::InvalidResponse("audience mismatch"));
+    }
+
+    if claims.exp <= now {
+        return Err(Error::InvalidResponse("expired ID token"));
+    }
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(Error::InvalidResponse("nonce mismatch"));
+    }
+
+    Ok(claims)
+}
+
+/// Identity provider on localhost serving discovery and a token endpoint
+/// that checks the PKCE code verifier.
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::{get, post};
+    use axum::{Form, Json, Router};
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    const CODE: &str = "code";
+
+    /// What the authorization endpoint saw when the user logged in.
+    struct Login {
+        code_challenge: String,
+        nonce: String,
+        subject: String,
+    }
+
+    struct Provider {
+        issuer: String,
+        client_id: String,
+        login: Mutex<Option<Login>>,
+    }
+
+    pub(crate) struct MockProvider {
+        pub config: Config,
+        provider: Arc<Provider>,
+    }
+
+    impl MockProvider {
+        pub async fn start() -> Self {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let issuer = format!("http://{}", listener.local_addr().unwrap());
+            let config = Config {
+                issuer: issuer.clone(),
+                client_id: "todo".to_owned(),
+                client_secret: None,
+                redirect_url: "https://todo.example.com/user/oidc/callback".to_owned(),
+                auto_provision: false,
+            };
+            let provider = Arc::new(Provider {
+                issuer,
+                client_id: config.client_id.clone(),
+                login: Mutex::new(None),
+            });
+
+            let router = Router::new()
+                .route("/.well-known/openid-configuration", get(discovery))
+                .route("/token", post(token))
+                .with_state(provider.clone());
+            tokio::spawn(async move { axum::serve(listener, router).await });
+
+            MockProvider { config, provider }
+        }
+
+        /// Plays the user logging in as `subject` at the authorization URL
+        /// and returns the code the provider redirects back with.
+        pub fn authorize(&self, url: &str, subject: &str) -> String {
+            let url = reqwest::Url::parse(url).unwrap();
+            let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+            assert_eq!(url.path(), "/authorize");
+            assert_eq!(query["client_id"], self.config.client_id);
+            assert_eq!(query["redirect_uri"], self.config.redirect_url);
+            assert_eq!(query["code_challenge_method"], "S256");
+
+            *self.provider.login.lock().unwrap() = Some(Login {
+                code_challenge: query["code_challenge"].clone(),
+                nonce: query["nonce"].clone(),
+                subject: subject.to_owned(),
+            });
+            CODE.to_owned()
+        }
+
+        /// Goes through the whole flow and returns the validated claims.
+        pub async fn login(&self, subject: &str) -> Claims {
+            let (url, pending) = authorization_url(&self.config).await.unwrap();
+            let code = self.authorize(&url, subject);
+            exchange_code(&self.config, &code, &pending).await.unwrap()
+        }
+    }
+
+    async fn discovery(State(provider): State<Arc<Provider>>) -> Json<Value> {
+        Json(json!({
+            "issuer": provider.issuer,
+            "authorization_endpoint": format!("{}/authorize", provider.issuer),
+            "token_endpoint": format!("{}/token", provider.issuer),
+        }))
+    }
+
+    async fn token(
+        State(provider): State<Arc<Provider>>,
+        Form(params): Form<HashMap<String, String>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        let login = provider
+            .login
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or(StatusCode::BAD_REQUEST)?;
+
+        let code_challenge = params
+            .get("code_verifier")
+            .map(|verifier| URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())));
+        if params.get("grant_type").map(String::as_str) != Some("authorization_code")
+            || params.get("code").map(String::as_str) != Some(CODE)
+            || params.get("client_id") != Some(&provider.client_id)
+            || code_challenge != Some(login.code_challenge)
+        {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let claims = json!({
+            "iss": provider.issuer,
+            "aud": provider.client_id,
+            "exp": chrono::Utc::now().timestamp() + 60,
+            "nonce": login.nonce,
+            "sub": login.subject,
+            "preferred_username": login.subject,
+        });
+        let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "RS256" }).to_string());
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        Ok(Json(
+            json!({ "id_token": format!("{header}.{payload}.signature") }),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn config() -> Config {
+        Config {
+            issuer: "https://id.example.com".to_owned(),
+            client_id: "todo".to_owned(),
+            client_secret: None,
+            redirect_url: "https://todo.example.com/user/oidc/callback".to_owned(),
+            auto_provision: false,
+        }
+    }
+
+    fn claims() -> Value {
+        json!({
+            "iss": "https://id.example.com/",
+            "aud": "todo",
+            "exp": NOW + 60,
+            "nonce": "nonce",
+            "sub": "1234",
+            "preferred_username": "alice",
+            "email": "alice@example.com",
+        })
+    }
+
+    /// An unsigned ID token, the signature not being checked.
+    fn id_token(claims: &Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "RS256" }).to_string());
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        format!("{header}.{payload}.signature")
+    }
+
+    fn validate(claims: &Value) -> Result<Claims, Error> {
+        validate_id_token(&id_token(claims), &config(), "nonce", NOW)
+    }
+
+    fn rejection(claims: &Value) -> &'static str {
+        match validate(claims) {
+            Err(Error::InvalidResponse(reason)) => reason,
+            result => panic!("expected an invalid response, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_claims() {
+        let claims = validate(&claims()).unwrap();
+
+        assert_eq!(claims.sub, "1234");
+        assert_eq!(claims.preferred_username.as_deref(), Some("alice"));
+        assert_eq!(claims.email.as_deref(), Some("alice@example.com"));
+    }
+
+    #[test]
+    fn accepts_the_client_among_several_audiences() {
+        let mut claims = claims();
+        claims["aud"] = json!(["other", "todo"]);
+
+        assert!(validate(&claims).is_ok());
+    }
+
+    #[test]
+    fn rejects_another_issuer() {
+        let mut claims = claims();
+        claims["iss"] = json!("https://evil.example.com");
+
+        assert_eq!(rejection(&claims), "issuer mismatch");
+    }
+
+    #[test]
+    fn rejects_another_audience() {
+        let mut claims = claims();
+        claims["aud"] = json!("other");
+        assert_eq!(rejection(&claims), "audience mismatch");
+
+        claims["aud"] = json!(["other"]);
+        assert_eq!(rejection(&claims), "audience mismatch");
+    }
+
+    #[test]
+    fn rejects_expired_tokens() {
+        let mut claims = claims();
+        claims["exp"] = json!(NOW);
+
+        assert_eq!(rejection(&claims), "expired ID token");
+    }
+
+    #[test]
+    fn rejects_another_or_missing_nonce() {
+        let mut claims = claims();
+        claims["nonce"] = json!("other");
+        assert_eq!(rejection(&claims), "nonce mismatch");
+
+        claims.as_object_mut().unwrap().remove("nonce");
+        assert_eq!(rejection(&claims), "nonce mismatch");
+    }
+
+    #[tokio::test]
+    async fn logs_in_through_the_provider() {
+        let provider = mock::MockProvider::start().await;
+
+        let claims = provider.login("alice").await;
+
+        assert_eq!(claims.sub, "alice");
+        assert_eq!(claims.preferred_username.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn rejects_another_code_verifier() {
+        let provider = mock::MockProvider::start().await;
+
+        let (url, mut pending) = authorization_url(&provider.config).await.unwrap();
+        let code = provider.authorize(&url, "alice");
+        pending.code_verifier = random();
+
+        assert!(matches!(
+            exchange_code(&provider.config, &code, &pending).await,
+            Err(Error::Http(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_another_issuer_at_discovery() {
+        let provider = mock::MockProvider::start().await;
+        let mut config = provider.config.clone();
+        config.issuer = config.issuer.replace("127.0.0.1", "localhost");
+
+        assert!(matches!(
+            authorization_url(&config).await,
+            Err(Error::InvalidResponse("issuer mismatch"))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        assert!(matches!(
+            validate_id_token("not a token", &config(), "nonce", NOW),
+            Err(Error::InvalidResponse("malformed ID token"))
+        ));
+        assert!(matches!(
+            validate_id_token("header.!!!.signature", &config(), "nonce", NOW),
+            Err(Error::InvalidResponse("malformed ID token"))
+        ));
+    }
+}
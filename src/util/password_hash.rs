@@ -3,19 +3,35 @@ use std::sync::LazyLock;
 
 use argon2::password_hash::rand_core::OsRng;
 use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
-use argon2::Argon2;
+use argon2::{Algorithm, Argon2, Params, Version};
 
-static SALT: LazyLock<SaltString> = LazyLock::new(|| {
-    env::var("SALT")
-        .map(|salt| SaltString::from_b64(&salt).expect("Invalid salt"))
-        .unwrap_or_else(|_| SaltString::generate(&mut OsRng))
+/// Argon2 parameters for new hashes, configurable through the
+/// `ARGON2_M_COST`, `ARGON2_T_COST` and `ARGON2_P_COST` env vars.
+static PARAMS: LazyLock<Params> = LazyLock::new(|| {
+    let cost = |name: &str, default: u32| {
+        env::var(name)
+            .map(|cost| cost.parse().expect("Invalid Argon2 cost"))
+            .unwrap_or(default)
+    };
+
+    Params::new(
+        cost("ARGON2_M_COST", Params::DEFAULT_M_COST),
+        cost("ARGON2_T_COST", Params::DEFAULT_T_COST),
+        cost("ARGON2_P_COST", Params::DEFAULT_P_COST),
+        None,
+    )
+    .expect("Invalid Argon2 parameters")
 });
 
+fn argon2() -> Argon2<'static> {
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, PARAMS.clone())
+}
+
 pub fn hash_password(password: &str) -> String {
-    let argon2 = Argon2::default();
+    let salt = SaltString::generate(&mut OsRng);
 
-    let password_hash = argon2
-        .hash_password(password.as_bytes(), &*SALT)
+    let password_hash = argon2()
+        .hash_password(password.as_bytes(), &salt)
         .expect("Failed to hash password");
 
     password_hash.to_string()
@@ -30,3 +46,26 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
         .verify_password(password.as_bytes(), &password_hash)
         .is_ok()
 }
+
+/// Whether the hash was made with another algorithm or version, or weaker
+/// parameters than the configured ones.
+pub fn needs_rehash(hash: &str) -> bool {
+    let Ok(password_hash) = PasswordHash::new(hash) else {
+        return true;
+    };
+
+    if password_hash.algorithm != Algorithm::Argon2id.ident()
+        || password_hash.version != Some(Version::V0x13.into())
+    {
+        return true;
+    }
+
+    match Params::try_from(&password_hash) {
+        Ok(params) => {
+            params.m_cost() < PARAMS.m_cost()
+                || params.t_cost() < PARAMS.t_cost()
+                || params.p_cost() < PARAMS.p_cost()
+        }
+        Err(_) => true,
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// Failures allowed before any backoff applies.
+const FREE_ATTEMPTS: u32 = 3;
+/// Failures after which the key is locked out.
+const LOCKOUT_ATTEMPTS: u32 = 10;
+const BASE_DELAY: Duration = Duration::from_secs(1);
+const LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy)]
+struct Attempts {
+    failures: u32,
+    last_failure: Instant,
+}
+
+impl Attempts {
+    /// Instant before which no further attempt is allowed.
+    fn blocked_until(&self) -> Instant {
+        if self.failures >= LOCKOUT_ATTEMPTS {
+            return self.last_failure + LOCKOUT;
+        }
+
+        let exponent = self.failures.saturating_sub(FREE_ATTEMPTS);
+        if exponent == 0 {
+            return self.last_failure;
+        }
+
+        self.last_failure + BASE_DELAY * 2u32.pow(exponent - 1)
+    }
+}
+
+/// Tracks failed attempts per key, with exponential backoff once the free
+/// attempts are spent and a temporary lockout after repeated failures.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    attempts: Mutex<HashMap<String, Attempts>>,
+}
+
+impl RateLimiter {
+    /// Returns how long the caller must wait before trying again, if at all.
+    pub fn check(&self, key: &str, now: Instant) -> Option<Duration> {
+        let attempts = self.attempts.lock();
+        let blocked_until = attempts.get(key)?.blocked_until();
+
+        (blocked_until > now).then(|| blocked_until - now)
+    }
+
+    pub fn record_failure(&self, key: &str, now: Instant) {
+        let mut attempts = self.attempts.lock();
+
+        // Forget keys that have been quiet for longer than a lockout.
+        attempts.retain(|_, attempts| now.duration_since(attempts.last_failure) < LOCKOUT);
+
+        let attempts = attempts.entry(key.to_owned()).or_insert(Attempts {
+            failures: 0,
+            last_failure: now,
+        });
+
+        attempts.failures += 1;
+        attempts.last_failure = now;
+    }
+
+    pub fn record_success(&self, key: &str) {
+        self.attempts.lock().remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail(limiter: &RateLimiter, times: u32, now: Instant) {
+        for _ in 0..times {
+            limiter.record_failure("alice", now);
+        }
+    }
+
+    #[test]
+    fn allows_the_free_attempts() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        assert_eq!(limiter.check("alice", now), None);
+        fail(&limiter, FREE_ATTEMPTS, now);
+        assert_eq!(limiter.check("alice", now), None);
+    }
+
+    #[test]
+    fn doubles_the_delay_after_each_failure() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        fail(&limiter, FREE_ATTEMPTS, now);
+
+        for delay in [1, 2, 4, 8, 16, 32] {
+            limiter.record_failure("alice", now);
+            assert_eq!(
+                limiter.check("alice", now),
+                Some(Duration::from_secs(delay))
+            );
+        }
+
+        let later = now + Duration::from_millis(32_500);
+        assert_eq!(
+            limiter.check("alice", later),
+            None,
+            "the delay is counted from the last failure"
+        );
+    }
+
+    #[test]
+    fn locks_out_after_repeated_failures() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        fail(&limiter, LOCKOUT_ATTEMPTS - 1, now);
+        assert!(limiter.check("alice", now).unwrap() < LOCKOUT);
+
+        limiter.record_failure("alice", now);
+        assert_eq!(limiter.check("alice", now), Some(LOCKOUT));
+        assert_eq!(
+            limiter.check("alice", now + Duration::from_secs(60)),
+            Some(LOCKOUT - Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn lockout_expires() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        fail(&limiter, LOCKOUT_ATTEMPTS, now);
+
+        let later = now + LOCKOUT;
+        assert_eq!(limiter.check("alice", later), None);
+
+        // Failures from before the lockout are forgotten.
+        limiter.record_failure("alice", later);
+        assert_eq!(limiter.check("alice", later), None);
+    }
+
+    #[test]
+    fn success_resets_the_failures() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        fail(&limiter, LOCKOUT_ATTEMPTS, now);
+
+        limiter.record_success("alice");
+        assert_eq!(limiter.check("alice", now), None);
+
+        fail(&limiter, FREE_ATTEMPTS, now);
+        assert_eq!(limiter.check("alice", now), None);
+    }
+
+    #[test]
+    fn keys_are_independent() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        fail(&limiter, LOCKOUT_ATTEMPTS, now);
+
+        assert_eq!(limiter.check("bob", now), None);
+    }
+}
@@ -0,0 +1,604 @@
+//! The subset of RFC 5545 recurrence rules tasks can repeat with, such as
+//! `FREQ=WEEKLY;BYDAY=MO` or `FREQ=MONTHLY;BYMONTHDAY=1`.
+//!
+//! Supported parts are `FREQ` (daily, weekly, monthly or yearly),
+//! `INTERVAL`, `BYDAY` without ordinals for weekly rules, `BYMONTHDAY` for
+//! monthly rules, and either `COUNT` or a date-only `UNTIL`.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "server")]
+use chrono::{Datelike, Days, Months};
+use chrono::{NaiveDate, Weekday};
+
+/// Monthly and yearly rules give up after this many periods without a
+/// matching day, such as the 31st every 12 months from February.
+#[cfg(feature = "server")]
+const MAX_SKIPPED_PERIODS: u32 = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<Weekday>,
+    /// Days of the month, negative ones counting from the end.
+    pub by_month_day: Vec<i8>,
+    /// Total number of occurrences, the first one included.
+    pub count: Option<u32>,
+    /// Last day an occurrence may fall on.
+    pub until: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    #[error("FREQ is required")]
+    MissingFrequency,
+
+    #[error("{0} appears more than once")]
+    DuplicatePart(String),
+
+    #[error("{0} is not supported")]
+    UnsupportedPart(String),
+
+    #[error("Invalid {part} value: {value}")]
+    InvalidValue { part: String, value: String },
+
+    #[error("BYDAY is only supported with FREQ=WEEKLY")]
+    ByDayWithoutWeekly,
+
+    #[error("BYMONTHDAY is only supported with FREQ=MONTHLY")]
+    ByMonthDayWithoutMonthly,
+
+    #[error("COUNT and UNTIL cannot be used together")]
+    CountAndUntil,
+}
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    WEEKDAYS
+        .iter()
+        .find(|(_, w)| *w == weekday)
+        .map(|(code, _)| *code)
+        .unwrap()
+}
+
+impl FromStr for Recurrence {
+    type Err = ParseError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+        let mut frequency = None;
+        let mut interval = None;
+        let mut by_day = None;
+        let mut by_month_day = None;
+        let mut count = None;
+        let mut until = None;
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| ParseError::InvalidValue {
+                    part: part.to_owned(),
+                    value: String::new(),
+                })?;
+            let name = name.trim().to_uppercase();
+            let value = value.trim().to_uppercase();
+
+            let invalid = || ParseError::InvalidValue {
+                part: name.clone(),
+                value: value.clone(),
+            };
+
+            let duplicate = match name.as_str() {
+                "FREQ" => frequency
+                    .replace(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(invalid()),
+                    })
+                    .is_some(),
+                "INTERVAL" => interval
+                    .replace(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|&n| n > 0)
+                            .ok_or_else(invalid)?,
+                    )
+                    .is_some(),
+                "BYDAY" => by_day
+                    .replace(
+                        value
+                            .split(',')
+                            .map(|code| {
+                                WEEKDAYS
+                                    .iter()
+                                    .find(|(c, _)| *c == code)
+                                    .map(|(_, weekday)| *weekday)
+                                    .ok_or_else(invalid)
+                            })
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )
+                    .is_some(),
+                "BYMONTHDAY" => by_month_day
+                    .replace(
+                        value
+                            .split(',')
+                            .map(|day| {
+                                day.parse::<i8>()
+                                    .ok()
+                                    .filter(|day| (1..=31).contains(&day.unsigned_abs()))
+                                    .ok_or_else(invalid)
+                            })
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )
+                    .is_some(),
+                "COUNT" => count
+                    .replace(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|&n| n > 0)
+                            .ok_or_else(invalid)?,
+                    )
+                    .is_some(),
+                "UNTIL" => until
+                    .replace(
+                        NaiveDate::parse_from_str(value.trim_end_matches('Z'), "%Y%m%d")
+                            .or_else(|_| {
+                                chrono::NaiveDateTime::parse_from_str(
+                                    value.trim_end_matches('Z'),
+                                    "%Y%m%dT%H%M%S",
+                                )
+                                .map(|until| until.date())
+                            })
+                            .map_err(|_| invalid())?,
+                    )
+                    .is_some(),
+                _ => return Err(ParseError::UnsupportedPart(name.clone())),
+            };
+
+            if duplicate {
+                return Err(ParseError::DuplicatePart(name));
+            }
+        }
+
+        let frequency = frequency.ok_or(ParseError::MissingFrequency)?;
+        let mut by_day = by_day.unwrap_or_default();
+        let mut by_month_day = by_month_day.unwrap_or_default();
+
+        if !by_day.is_empty() && frequency != Frequency::Weekly {
+            return Err(ParseError::ByDayWithoutWeekly);
+        }
+        if !by_month_day.is_empty() && frequency != Frequency::Monthly {
+            return Err(ParseError::ByMonthDayWithoutMonthly);
+        }
+        if count.is_some() && until.is_some() {
+            return Err(ParseError::CountAndUntil);
+        }
+
+        by_day.sort_by_key(Weekday::num_days_from_monday);
+        by_day.dedup();
+        by_month_day.sort();
+        by_month_day.dedup();
+
+        Ok(Recurrence {
+            frequency,
+            interval: interval.unwrap_or(1),
+            by_day,
+            by_month_day,
+            count,
+            until,
+        })
+    }
+}
+
+/// Formats the rule back into its canonical RRULE form.
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self.by_day.iter().map(|&day| weekday_code(day)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(i8::to_string).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "server")]
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap()
+}
+
+#[cfg(feature = "server")]
+fn days_in_month(month: NaiveDate) -> u32 {
+    // Only December of the last representable year has no next month.
+    first_of_month(month)
+        .checked_add_months(Months::new(1))
+        .map_or(31, |next| (next - Days::new(1)).day())
+}
+
+#[cfg(feature = "server")]
+impl Recurrence {
+    /// Days of `month` the rule falls on, in order.
+    fn month_days(&self, month: NaiveDate, anchor: NaiveDate) -> Vec<NaiveDate> {
+        let last = days_in_month(month) as i8;
+
+        let by_month_day = if self.by_month_day.is_empty() {
+            vec![anchor.day() as i8]
+        } else {
+            self.by_month_day.clone()
+        };
+
+        let mut days: Vec<NaiveDate> = by_month_day
+            .into_iter()
+            .map(|day| if day < 0 { last + day + 1 } else { day })
+            .filter(|day| (1..=last).contains(day))
+            .filter_map(|day| month.with_day(day as u32))
+            .collect();
+
+        days.sort();
+        days
+    }
+
+    /// Day of the occurrence following the one on `date`, ignoring `COUNT`.
+    /// `None` once the rule is over.
+    pub fn next_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let next = match self.frequency {
+            Frequency::Daily => date.checked_add_days(Days::new(self.interval.into())),
+            Frequency::Weekly if self.by_day.is_empty() => {
+                date.checked_add_days(Days::new(7 * u64::from(self.interval)))
+            }
+            Frequency::Weekly => {
+                let weekday = u64::from(date.weekday().num_days_from_monday());
+                let week_start = date.checked_sub_days(Days::new(weekday))?;
+                let rest_of_week =
+                    (1..7 - weekday).filter_map(|n| date.checked_add_days(Days::new(n)));
+                let next_week = week_start
+                    .checked_add_days(Days::new(7 * u64::from(self.interval)))
+                    .into_iter()
+                    .flat_map(|start| {
+                        (0..7).filter_map(move |n| start.checked_add_days(Days::new(n)))
+                    });
+
+                rest_of_week
+                    .chain(next_week)
+                    .find(|day| self.by_day.contains(&day.weekday()))
+            }
+            Frequency::Monthly => {
+                let month = first_of_month(date);
+                self.month_days(month, date)
+                    .into_iter()
+                    .find(|&day| day > date)
+                    .or_else(|| {
+                        (1..=MAX_SKIPPED_PERIODS).find_map(|n| {
+                            let months = n.checked_mul(self.interval)?;
+                            let month = month.checked_add_months(Months::new(months))?;
+                            self.month_days(month, date).into_iter().next()
+                        })
+                    })
+            }
+            Frequency::Yearly => (1..=MAX_SKIPPED_PERIODS).find_map(|n| {
+                let years = i32::try_from(n.checked_mul(self.interval)?).ok()?;
+                let year = date.year().checked_add(years)?;
+                NaiveDate::from_ymd_opt(year, date.month(), date.day())
+            }),
+        }?;
+
+        match self.until {
+            Some(until) if next > until => None,
+            _ => Some(next),
+        }
+    }
+}
+
+impl Recurrence {
+    /// Short human description, such as "every 2 weeks on Mon, Thu".
+    pub fn describe(&self) -> String {
+        let unit = match self.frequency {
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+            Frequency::Yearly => "year",
+        };
+
+        let mut description = match self.interval {
+            1 => format!("every {unit}"),
+            n => format!("every {n} {unit}s"),
+        };
+
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self.by_day.iter().map(Weekday::to_string).collect();
+            description.push_str(&format!(" on {}", days.join(", ")));
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self
+                .by_month_day
+                .iter()
+                .map(|&day| match day {
+                    -1 => "the last day".to_owned(),
+                    day if day < 0 => format!("{} days before the last day", -day - 1),
+                    day => format!("day {day}"),
+                })
+                .collect();
+            description.push_str(&format!(" on {}", days.join(", ")));
+        }
+        if let Some(count) = self.count {
+            description.push_str(&format!(", {count} times"));
+        }
+        if let Some(until) = self.until {
+            description.push_str(&format!(", until {}", until.format("%Y-%m-%d")));
+        }
+
+        description
+    }
+}
+
+/// Checks the value of a repeat input, which may be left empty.
+pub fn parse_recurrence_input(rule: &str) -> Result<Option<Recurrence>, ParseError> {
+    match rule.trim() {
+        "" => Ok(None),
+        rule => rule.parse().map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn rule(rule: &str) -> Recurrence {
+        rule.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_every_part() {
+        let recurrence = rule("RRULE:freq=weekly;interval=2;byday=FR,MO,MO;until=20250630T000000Z");
+
+        assert_eq!(
+            recurrence,
+            Recurrence {
+                frequency: Frequency::Weekly,
+                interval: 2,
+                by_day: vec![Weekday::Mon, Weekday::Fri],
+                by_month_day: Vec::new(),
+                count: None,
+                until: Some(date(2025, 6, 30)),
+            }
+        );
+        assert_eq!(
+            recurrence.to_string(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR;UNTIL=20250630"
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        for text in [
+            "FREQ=DAILY",
+            "FREQ=WEEKLY;BYDAY=MO,WE",
+            "FREQ=MONTHLY;BYMONTHDAY=-1,1",
+            "FREQ=YEARLY;INTERVAL=2;COUNT=3",
+        ] {
+            assert_eq!(rule(text).to_string(), text);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let error = |rule: &str| rule.parse::<Recurrence>().unwrap_err();
+
+        assert_eq!(error("INTERVAL=2"), ParseError::MissingFrequency);
+        assert_eq!(
+            error("FREQ=DAILY;FREQ=WEEKLY"),
+            ParseError::DuplicatePart("FREQ".to_owned())
+        );
+        assert_eq!(
+            error("FREQ=DAILY;BYHOUR=9"),
+            ParseError::UnsupportedPart("BYHOUR".to_owned())
+        );
+        assert_eq!(
+            error("FREQ=DAILY;INTERVAL=0"),
+            ParseError::InvalidValue {
+                part: "INTERVAL".to_owned(),
+                value: "0".to_owned(),
+            }
+        );
+        assert_eq!(
+            error("FREQ=MONTHLY;BYMONTHDAY=32"),
+            ParseError::InvalidValue {
+                part: "BYMONTHDAY".to_owned(),
+                value: "32".to_owned(),
+            }
+        );
+        assert_eq!(error("FREQ=DAILY;BYDAY=MO"), ParseError::ByDayWithoutWeekly);
+        assert_eq!(
+            error("FREQ=WEEKLY;BYMONTHDAY=1"),
+            ParseError::ByMonthDayWithoutMonthly
+        );
+        assert_eq!(
+            error("FREQ=DAILY;COUNT=2;UNTIL=20250101"),
+            ParseError::CountAndUntil
+        );
+    }
+
+    #[test]
+    fn parses_count() {
+        assert_eq!(rule("FREQ=DAILY;COUNT=5").count, Some(5));
+        assert!("FREQ=DAILY;COUNT=0".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn repeats_daily_and_weekly() {
+        let recurrence = rule("FREQ=DAILY;INTERVAL=3");
+        assert_eq!(
+            recurrence.next_after(date(2025, 2, 27)),
+            Some(date(2025, 3, 2))
+        );
+
+        let recurrence = rule("FREQ=WEEKLY");
+        assert_eq!(
+            recurrence.next_after(date(2025, 4, 2)),
+            Some(date(2025, 4, 9))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn wraps_weekly_days_around_to_the_next_period() {
+        let recurrence = rule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR");
+
+        // Wednesday, then Friday of the same week.
+        assert_eq!(
+            recurrence.next_after(date(2025, 4, 2)),
+            Some(date(2025, 4, 4))
+        );
+        // Friday, then Monday two weeks after the start of the week.
+        assert_eq!(
+            recurrence.next_after(date(2025, 4, 4)),
+            Some(date(2025, 4, 14))
+        );
+        // Sunday, the last day of the week.
+        assert_eq!(
+            recurrence.next_after(date(2025, 4, 6)),
+            Some(date(2025, 4, 14))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn skips_months_without_the_day() {
+        let recurrence = rule("FREQ=MONTHLY");
+        assert_eq!(
+            recurrence.next_after(date(2025, 1, 31)),
+            Some(date(2025, 3, 31))
+        );
+        assert_eq!(
+            recurrence.next_after(date(2025, 3, 31)),
+            Some(date(2025, 5, 31))
+        );
+
+        let recurrence = rule("FREQ=YEARLY");
+        assert_eq!(
+            recurrence.next_after(date(2024, 2, 29)),
+            Some(date(2028, 2, 29))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn counts_month_days_from_the_end() {
+        let recurrence = rule("FREQ=MONTHLY;BYMONTHDAY=-1");
+
+        assert_eq!(
+            recurrence.next_after(date(2025, 1, 31)),
+            Some(date(2025, 2, 28))
+        );
+        assert_eq!(
+            recurrence.next_after(date(2024, 1, 31)),
+            Some(date(2024, 2, 29))
+        );
+        assert_eq!(
+            recurrence.next_after(date(2025, 2, 10)),
+            Some(date(2025, 2, 28))
+        );
+
+        let recurrence = rule("FREQ=MONTHLY;BYMONTHDAY=1,15");
+        assert_eq!(
+            recurrence.next_after(date(2025, 2, 1)),
+            Some(date(2025, 2, 15))
+        );
+        assert_eq!(
+            recurrence.next_after(date(2025, 2, 15)),
+            Some(date(2025, 3, 1))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn stops_after_until() {
+        let recurrence = rule("FREQ=WEEKLY;UNTIL=20250410");
+
+        assert_eq!(
+            recurrence.next_after(date(2025, 4, 2)),
+            Some(date(2025, 4, 9))
+        );
+        assert_eq!(recurrence.next_after(date(2025, 4, 9)), None);
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn gives_up_on_huge_intervals() {
+        let day = date(2025, 4, 7);
+
+        for text in [
+            "FREQ=DAILY;INTERVAL=4000000000",
+            "FREQ=WEEKLY;INTERVAL=4000000000",
+            "FREQ=WEEKLY;BYDAY=MO;INTERVAL=4000000000",
+            "FREQ=MONTHLY;INTERVAL=100000000",
+            "FREQ=MONTHLY;BYMONTHDAY=1;INTERVAL=100000000",
+            "FREQ=YEARLY;INTERVAL=100000000",
+        ] {
+            assert_eq!(rule(text).next_after(day), None, "{text}");
+        }
+
+        assert_eq!(rule("FREQ=DAILY").next_after(NaiveDate::MAX), None);
+        assert_eq!(
+            rule("FREQ=WEEKLY;BYDAY=MO").next_after(NaiveDate::MAX),
+            None
+        );
+        assert_eq!(rule("FREQ=MONTHLY").next_after(NaiveDate::MAX), None);
+    }
+}
@@ -0,0 +1,50 @@
+//! Signed tokens such as invitation links, keyed with the `SECRET` env var.
+//!
+//! `SECRET` must be at least 32 bytes long and stay the same across restarts,
+//! or every link handed out before would stop working. It can be generated
+//! with `openssl rand -base64 32`.
+
+use std::env;
+use std::sync::LazyLock;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SECRET_MIN_LENGTH: usize = 32;
+
+/// Forced at startup so that a missing or short secret fails right away.
+pub static SECRET: LazyLock<Vec<u8>> = LazyLock::new(|| {
+    let secret = env::var("SECRET").expect("SECRET is required");
+    assert!(
+        secret.len() >= SECRET_MIN_LENGTH,
+        "SECRET must be at least {SECRET_MIN_LENGTH} bytes long"
+    );
+    secret.into_bytes()
+});
+
+fn mac(payload: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(&SECRET).expect("HMAC accepts any key length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// Signs the payload, returning a `<payload>.<signature>` token.
+pub fn sign(payload: &str) -> String {
+    let signature = URL_SAFE_NO_PAD.encode(mac(payload).finalize().into_bytes());
+    format!("{payload}.{signature}")
+}
+
+/// Returns the payload of a token produced by [`sign`] if its signature is
+/// valid.
+pub fn verify(token: &str) -> Option<&str> {
+    let (payload, signature) = token.rsplit_once('.')?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+
+    mac(payload).verify_slice(&signature).ok()?;
+
+    Some(payload)
+}
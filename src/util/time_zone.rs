@@ -0,0 +1,58 @@
+//! Conversions between the instants stored in the database and the wall
+//! clock of a user, shared by the server and the forms.
+
+#[cfg(feature = "server")]
+use chrono::{DateTime, TimeZone, Utc};
+use chrono::{NaiveDate, NaiveTime};
+#[cfg(feature = "server")]
+use chrono_tz::Tz;
+
+/// Parses an IANA time zone name such as `Europe/Paris`.
+#[cfg(feature = "server")]
+pub fn parse_time_zone(name: &str) -> Option<Tz> {
+    name.parse().ok()
+}
+
+/// Instant of `time` on `date` in `tz`. When clocks go back, the earlier of
+/// the two instants is used; times skipped when clocks go forward are `None`.
+#[cfg(feature = "server")]
+pub fn to_utc(date: NaiveDate, time: NaiveTime, tz: Tz) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+}
+
+/// First instant of `date` in `tz`, which isn't midnight on days clocks go
+/// forward at midnight.
+#[cfg(feature = "server")]
+pub fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    tz.from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&date.and_hms_opt(1, 0, 0).unwrap())
+                .earliest()
+        })
+        .map(|local| local.with_timezone(&Utc))
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc())
+}
+
+/// Current day in `tz`.
+#[cfg(feature = "server")]
+pub fn today(tz: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&tz).date_naive()
+}
+
+/// Parses the values of a `date` and a `time` input, either of which may be
+/// left empty. `None` when a non-empty value is malformed.
+pub fn parse_due_inputs(date: &str, time: &str) -> Option<(Option<NaiveDate>, Option<NaiveTime>)> {
+    let date = match date {
+        "" => None,
+        date => Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?),
+    };
+    let time = match time {
+        "" => None,
+        time => Some(NaiveTime::parse_from_str(time, "%H:%M").ok()?),
+    };
+
+    Some((date, time))
+}
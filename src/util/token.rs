@@ -0,0 +1,14 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use data_encoding::{BASE32_NOPAD, HEXLOWER};
+use sha2::{Digest, Sha256};
+
+/// Random secret token, long enough to be stored with a plain SHA-256.
+pub fn generate_token() -> String {
+    let mut bytes = [0; 20];
+    OsRng.fill_bytes(&mut bytes);
+    format!("todo_{}", BASE32_NOPAD.encode(&bytes).to_lowercase())
+}
+
+pub fn hash_token(token: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,317 @@
+//! Username and password rules, shared by the server and the forms.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+pub const USERNAME_MIN_LENGTH: usize = 3;
+pub const USERNAME_MAX_LENGTH: usize = 32;
+pub const PASSWORD_MIN_LENGTH: usize = 8;
+pub const PASSWORD_MAX_LENGTH: usize = 128;
+#[cfg(feature = "server")]
+pub const EMAIL_MAX_LENGTH: usize = 254;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Field {
+    Username,
+    Password,
+    Email,
+}
+
+impl Field {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Field::Username => "username",
+            Field::Password => "password",
+            Field::Email => "email",
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = ();
+
+    fn from_str(field: &str) -> Result<Self, Self::Err> {
+        [Field::Username, Field::Password, Field::Email]
+            .into_iter()
+            .find(|f| f.as_str() == field)
+            .ok_or(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: Field,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: Field, message: impl Into<String>) -> Self {
+        FieldError {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+/// Errors to show next to each form field. Formatted as one
+/// `field: message` line per error so it can travel as a server function
+/// error.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldErrors(pub Vec<FieldError>);
+
+impl FieldErrors {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Messages for the given field.
+    pub fn get(&self, field: Field) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|error| error.field == field)
+            .map(|error| error.message.clone())
+            .collect()
+    }
+}
+
+impl fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self
+            .0
+            .iter()
+            .map(|error| format!("{}: {}", error.field.as_str(), error.message))
+            .collect();
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl FromStr for FieldErrors {
+    type Err = ();
+
+    fn from_str(errors: &str) -> Result<Self, Self::Err> {
+        errors
+            .lines()
+            .map(|line| {
+                let (field, message) = line.split_once(": ").ok_or(())?;
+                Ok(FieldError::new(field.parse()?, message))
+            })
+            .collect::<Result<_, _>>()
+            .map(FieldErrors)
+    }
+}
+
+fn is_username_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-')
+}
+
+/// Trims and case-folds a username so "Alice" and "alice " are the same.
+pub fn normalize_username(username: &str) -> String {
+    username.trim().to_lowercase()
+}
+
+/// Turns an arbitrary name, such as one from an identity provider, into a
+/// valid username.
+#[cfg(feature = "server")]
+pub fn sanitize_username(name: &str) -> String {
+    let username: String = normalize_username(name)
+        .chars()
+        .map(|c| if is_username_char(c) { c } else { '-' })
+        .take(USERNAME_MAX_LENGTH)
+        .collect();
+
+    if username.len() < USERNAME_MIN_LENGTH {
+        format!("{username:-<USERNAME_MIN_LENGTH$}")
+    } else {
+        username
+    }
+}
+
+/// Checks an already normalized username.
+pub fn validate_username(username: &str) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    let length = username.chars().count();
+    if !(USERNAME_MIN_LENGTH..=USERNAME_MAX_LENGTH).contains(&length) {
+        errors.push(FieldError::new(
+            Field::Username,
+            format!(
+                "Must be between {USERNAME_MIN_LENGTH} and {USERNAME_MAX_LENGTH} characters long"
+            ),
+        ));
+    }
+
+    if !username.chars().all(is_username_char) {
+        errors.push(FieldError::new(
+            Field::Username,
+            "May only contain letters, digits, dots, dashes and underscores",
+        ));
+    }
+
+    errors
+}
+
+pub fn validate_password(password: &str, username: &str) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    let length = password.chars().count();
+    if length < PASSWORD_MIN_LENGTH {
+        errors.push(FieldError::new(
+            Field::Password,
+            format!("Must be at least {PASSWORD_MIN_LENGTH} characters long"),
+        ));
+    }
+    if length > PASSWORD_MAX_LENGTH {
+        errors.push(FieldError::new(
+            Field::Password,
+            format!("Must be at most {PASSWORD_MAX_LENGTH} characters long"),
+        ));
+    }
+
+    let has_letter = password.chars().any(char::is_alphabetic);
+    let has_other = password.chars().any(|c| !c.is_alphabetic());
+    if !has_letter || !has_other {
+        errors.push(FieldError::new(
+            Field::Password,
+            "Must contain a letter and a digit or symbol",
+        ));
+    }
+
+    if !username.is_empty() && password.to_lowercase().contains(username) {
+        errors.push(FieldError::new(
+            Field::Password,
+            "Must not contain the username",
+        ));
+    }
+
+    errors
+}
+
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Checks an already normalized email address. Only the overall shape is
+/// checked, whether it can receive mail is up to the mail server.
+#[cfg(feature = "server")]
+pub fn validate_email(email: &str) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    if email.len() > EMAIL_MAX_LENGTH {
+        errors.push(FieldError::new(
+            Field::Email,
+            format!("Must be at most {EMAIL_MAX_LENGTH} characters long"),
+        ));
+    }
+
+    let valid = email.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && !domain.contains('@')
+            && !email.chars().any(char::is_whitespace)
+    });
+    if !valid {
+        errors.push(FieldError::new(
+            Field::Email,
+            "Must be a valid email address",
+        ));
+    }
+
+    errors
+}
+
+/// Validates a registration, returning the normalized username.
+pub fn validate_registration(username: &str, password: &str) -> Result<String, FieldErrors> {
+    let username = normalize_username(username);
+
+    let mut errors = validate_username(&username);
+    errors.extend(validate_password(password, &username));
+
+    if errors.is_empty() {
+        Ok(username)
+    } else {
+        Err(FieldErrors(errors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|error| error.message.as_str()).collect()
+    }
+
+    #[test]
+    fn normalizes_usernames() {
+        assert_eq!(normalize_username("  Alice "), "alice");
+        assert_eq!(normalize_username("ALICE"), normalize_username("alice"));
+        // Case folding isn't limited to ASCII, the character rules then
+        // reject what's left.
+        assert_eq!(normalize_username("ÉLODIE"), "élodie");
+        assert!(!validate_username("élodie").is_empty());
+    }
+
+    #[test]
+    fn accepts_valid_usernames() {
+        for username in ["bob", "alice.smith", "a_b-c", "user42"] {
+            assert_eq!(validate_username(username), [], "{username}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_characters() {
+        for username in ["Alice", "al ice", "alice@home", "élodie", "al/ice"] {
+            assert_eq!(
+                messages(&validate_username(username)),
+                ["May only contain letters, digits, dots, dashes and underscores"],
+                "{username}"
+            );
+        }
+    }
+
+    #[test]
+    fn bounds_username_length() {
+        let length_error = "Must be between 3 and 32 characters long";
+        let at_most = "a".repeat(USERNAME_MAX_LENGTH);
+        let too_long = "a".repeat(USERNAME_MAX_LENGTH + 1);
+
+        assert_eq!(messages(&validate_username("ab")), [length_error]);
+        assert_eq!(validate_username("abc"), []);
+        assert_eq!(validate_username(&at_most), []);
+        assert_eq!(messages(&validate_username(&too_long)), [length_error]);
+        assert_eq!(messages(&validate_username("")), [length_error]);
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn sanitizes_provider_names() {
+        assert_eq!(sanitize_username("Alice Smith"), "alice-smith");
+        assert_eq!(sanitize_username("alice@example.com"), "alice-example.com");
+        assert_eq!(sanitize_username("Al"), "al-");
+        assert_eq!(
+            sanitize_username(&"a".repeat(40)).len(),
+            USERNAME_MAX_LENGTH
+        );
+
+        for name in ["Élodie", "x", "名前", &"Z".repeat(100)] {
+            assert_eq!(validate_username(&sanitize_username(name)), [], "{name}");
+        }
+    }
+
+    #[test]
+    fn registration_returns_the_normalized_username() {
+        assert_eq!(
+            validate_registration(" Alice ", "correct horse 1"),
+            Ok("alice".to_owned())
+        );
+
+        let errors = validate_registration("a", "bob").unwrap_err();
+        assert_eq!(errors.get(Field::Username).len(), 1);
+        assert_eq!(errors.get(Field::Password).len(), 2);
+    }
+}